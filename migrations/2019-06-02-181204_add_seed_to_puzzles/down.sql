@@ -0,0 +1,2 @@
+ALTER TABLE puzzles
+DROP COLUMN seed;
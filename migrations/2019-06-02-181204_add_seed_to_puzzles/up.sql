@@ -0,0 +1,3 @@
+ALTER TABLE puzzles
+ADD COLUMN IF NOT EXISTS
+seed BIGINT;
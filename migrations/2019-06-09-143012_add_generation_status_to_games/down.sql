@@ -0,0 +1,4 @@
+ALTER TABLE games
+DROP COLUMN generation_status;
+ALTER TABLE games
+DROP COLUMN generation_error;
@@ -0,0 +1,6 @@
+ALTER TABLE games
+ADD COLUMN IF NOT EXISTS
+generation_status VARCHAR NOT NULL DEFAULT 'succeeded';
+ALTER TABLE games
+ADD COLUMN IF NOT EXISTS
+generation_error VARCHAR;
@@ -0,0 +1,12 @@
+DELETE FROM solutions WHERE archive_id IS NOT NULL;
+DELETE FROM game_participations WHERE archive_id IS NOT NULL;
+
+DROP INDEX UC_SOLUTION;
+ALTER TABLE solutions ADD CONSTRAINT UC_SOLUTION UNIQUE (x1, y1, x2, y2, user_id, game_id);
+DROP INDEX UC_COMPLETION;
+ALTER TABLE game_participations ADD CONSTRAINT UC_COMPLETION UNIQUE (user_id, game_id);
+
+ALTER TABLE solutions DROP COLUMN archive_id;
+ALTER TABLE game_participations DROP COLUMN archive_id;
+
+DROP TABLE puzzle_archives;
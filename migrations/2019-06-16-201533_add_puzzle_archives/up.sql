@@ -0,0 +1,24 @@
+CREATE TABLE puzzle_archives(
+    id SERIAL PRIMARY KEY,
+    game_id INTEGER REFERENCES games(id) NOT NULL,
+    game_table VARCHAR NOT NULL,
+    table_columns INTEGER NOT NULL,
+    table_rows INTEGER NOT NULL,
+    solutions INTEGER[4][] NOT NULL,
+    words TEXT[] NOT NULL,
+    seed BIGINT,
+    archived_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now()
+);
+
+ALTER TABLE solutions
+ADD COLUMN archive_id INTEGER REFERENCES puzzle_archives(id);
+ALTER TABLE game_participations
+ADD COLUMN archive_id INTEGER REFERENCES puzzle_archives(id);
+
+-- Only the results of the current puzzle have to be unique
+ALTER TABLE solutions DROP CONSTRAINT UC_SOLUTION;
+CREATE UNIQUE INDEX UC_SOLUTION ON solutions (x1, y1, x2, y2, user_id, game_id)
+WHERE archive_id IS NULL;
+ALTER TABLE game_participations DROP CONSTRAINT UC_COMPLETION;
+CREATE UNIQUE INDEX UC_COMPLETION ON game_participations (user_id, game_id)
+WHERE archive_id IS NULL;
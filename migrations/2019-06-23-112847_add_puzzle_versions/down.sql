@@ -0,0 +1,33 @@
+DROP INDEX UC_SOLUTION;
+DROP INDEX UC_COMPLETION;
+
+-- Results of the current versions are not archived
+ALTER TABLE solutions ALTER COLUMN puzzle_version_id DROP NOT NULL;
+UPDATE solutions s
+SET puzzle_version_id = NULL
+FROM puzzles p
+WHERE p.version_id = s.puzzle_version_id;
+ALTER TABLE solutions RENAME COLUMN puzzle_version_id TO archive_id;
+CREATE UNIQUE INDEX UC_SOLUTION ON solutions (x1, y1, x2, y2, user_id, game_id)
+WHERE archive_id IS NULL;
+
+ALTER TABLE game_participations ALTER COLUMN puzzle_version_id DROP NOT NULL;
+UPDATE game_participations gp
+SET puzzle_version_id = NULL
+FROM puzzles p
+WHERE p.version_id = gp.puzzle_version_id;
+ALTER TABLE game_participations RENAME COLUMN puzzle_version_id TO archive_id;
+CREATE UNIQUE INDEX UC_COMPLETION ON game_participations (user_id, game_id)
+WHERE archive_id IS NULL;
+
+CREATE TEMPORARY TABLE current_versions AS
+SELECT version_id FROM puzzles WHERE version_id IS NOT NULL;
+ALTER TABLE puzzles DROP COLUMN version_id;
+DELETE FROM puzzle_versions WHERE id IN (SELECT version_id FROM current_versions);
+DROP TABLE current_versions;
+
+ALTER TABLE puzzle_versions DROP CONSTRAINT UC_PUZZLE_VERSION;
+ALTER TABLE puzzle_versions DROP COLUMN created_by;
+ALTER TABLE puzzle_versions DROP COLUMN version;
+ALTER TABLE puzzle_versions RENAME COLUMN created_at TO archived_at;
+ALTER TABLE puzzle_versions RENAME TO puzzle_archives;
@@ -0,0 +1,51 @@
+ALTER TABLE puzzle_archives RENAME TO puzzle_versions;
+-- The creation time of archived puzzles is not known, the time they were archived is used instead
+ALTER TABLE puzzle_versions RENAME COLUMN archived_at TO created_at;
+ALTER TABLE puzzle_versions ADD COLUMN version INTEGER;
+ALTER TABLE puzzle_versions ADD COLUMN created_by INTEGER REFERENCES users(id);
+
+-- Current puzzles become versions as well, including the ones being regenerated or whose
+-- regeneration failed, only puzzles never generated are left out
+INSERT INTO puzzle_versions (game_id, game_table, table_columns, table_rows, solutions, words, seed, created_at)
+SELECT p.game_id, p.game_table, p.table_columns, p.table_rows, p.solutions, p.words, p.seed, now()
+FROM puzzles p
+WHERE p.game_table <> '';
+
+UPDATE puzzle_versions v
+SET version = n.version, created_by = g.owner_id
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY game_id ORDER BY id) AS version
+    FROM puzzle_versions
+) n, games g
+WHERE v.id = n.id AND g.id = v.game_id;
+
+ALTER TABLE puzzle_versions ALTER COLUMN version SET NOT NULL;
+ALTER TABLE puzzle_versions ALTER COLUMN created_by SET NOT NULL;
+ALTER TABLE puzzle_versions ADD CONSTRAINT UC_PUZZLE_VERSION UNIQUE (game_id, version);
+
+ALTER TABLE puzzles ADD COLUMN version_id INTEGER REFERENCES puzzle_versions(id);
+UPDATE puzzles p
+SET version_id = (SELECT max(v.id) FROM puzzle_versions v WHERE v.game_id = p.game_id)
+WHERE p.game_table <> '';
+
+-- Bind every result to the version it was played on
+DROP INDEX UC_SOLUTION;
+DROP INDEX UC_COMPLETION;
+
+ALTER TABLE solutions RENAME COLUMN archive_id TO puzzle_version_id;
+-- Current results belong to the latest version of their game
+UPDATE solutions s
+SET puzzle_version_id = (SELECT max(v.id) FROM puzzle_versions v WHERE v.game_id = s.game_id)
+WHERE s.puzzle_version_id IS NULL;
+-- Games without any version never had a puzzle to play, so they can not have results
+DELETE FROM solutions WHERE puzzle_version_id IS NULL;
+ALTER TABLE solutions ALTER COLUMN puzzle_version_id SET NOT NULL;
+CREATE UNIQUE INDEX UC_SOLUTION ON solutions (x1, y1, x2, y2, user_id, puzzle_version_id);
+
+ALTER TABLE game_participations RENAME COLUMN archive_id TO puzzle_version_id;
+UPDATE game_participations gp
+SET puzzle_version_id = (SELECT max(v.id) FROM puzzle_versions v WHERE v.game_id = gp.game_id)
+WHERE gp.puzzle_version_id IS NULL;
+DELETE FROM game_participations WHERE puzzle_version_id IS NULL;
+ALTER TABLE game_participations ALTER COLUMN puzzle_version_id SET NOT NULL;
+CREATE UNIQUE INDEX UC_COMPLETION ON game_participations (user_id, puzzle_version_id);
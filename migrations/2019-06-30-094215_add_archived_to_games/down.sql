@@ -0,0 +1,5 @@
+DROP INDEX UC_Game;
+ALTER TABLE games ADD CONSTRAINT UC_Game UNIQUE (name, owner_id);
+
+ALTER TABLE games
+DROP COLUMN archived;
@@ -0,0 +1,8 @@
+ALTER TABLE games
+ADD COLUMN IF NOT EXISTS
+archived BOOLEAN NOT NULL DEFAULT false;
+
+-- Archived games do not block reusing their names
+ALTER TABLE games DROP CONSTRAINT UC_Game;
+CREATE UNIQUE INDEX UC_Game ON games (name, owner_id)
+WHERE NOT archived;
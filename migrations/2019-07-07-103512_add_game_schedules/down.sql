@@ -0,0 +1,4 @@
+ALTER TABLE games
+DROP COLUMN schedule_id;
+
+DROP TABLE game_schedules;
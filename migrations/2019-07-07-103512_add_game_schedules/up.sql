@@ -0,0 +1,19 @@
+CREATE TABLE game_schedules (
+    id SERIAL PRIMARY KEY,
+    owner_id INTEGER NOT NULL REFERENCES users(id),
+    name VARCHAR NOT NULL,
+    words TEXT[] NOT NULL,
+    words_per_game INTEGER NOT NULL,
+    time_zone VARCHAR NOT NULL,
+    start_day VARCHAR NOT NULL,
+    start_time TIME NOT NULL,
+    end_day VARCHAR NOT NULL,
+    end_time TIME NOT NULL,
+    -- Start of the next period a game has to be created for
+    next_start TIMESTAMP WITH TIME ZONE NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT true
+);
+
+ALTER TABLE games
+ADD COLUMN IF NOT EXISTS
+schedule_id INTEGER REFERENCES game_schedules(id);
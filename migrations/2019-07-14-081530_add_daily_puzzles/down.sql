@@ -0,0 +1,17 @@
+DROP TABLE daily_puzzles;
+
+-- The daily games, everything bound to them and their owner
+CREATE TEMPORARY TABLE system_games AS
+SELECT g.id
+FROM games g
+INNER JOIN users u ON u.id = g.owner_id
+WHERE u.googleid = 'perplexio-system';
+
+DELETE FROM solutions WHERE game_id IN (SELECT id FROM system_games);
+DELETE FROM game_participations WHERE game_id IN (SELECT id FROM system_games);
+DELETE FROM puzzles WHERE game_id IN (SELECT id FROM system_games);
+DELETE FROM puzzle_versions WHERE game_id IN (SELECT id FROM system_games);
+DELETE FROM games WHERE id IN (SELECT id FROM system_games);
+DROP TABLE system_games;
+
+DELETE FROM users WHERE googleid = 'perplexio-system';
@@ -0,0 +1,10 @@
+-- Owner of the games created by the server itself, it has no auth token so nobody can log in as it
+INSERT INTO users (name, googleid)
+VALUES ('Perplexio', 'perplexio-system')
+ON CONFLICT DO NOTHING;
+
+CREATE TABLE daily_puzzles (
+    date DATE PRIMARY KEY,
+    game_id INTEGER NOT NULL UNIQUE REFERENCES games(id),
+    theme VARCHAR NOT NULL
+);
@@ -0,0 +1,10 @@
+DROP INDEX IF EXISTS IX_GAME_TAGS;
+
+ALTER TABLE games
+DROP COLUMN difficulty;
+ALTER TABLE games
+DROP COLUMN language;
+ALTER TABLE games
+DROP COLUMN tags;
+ALTER TABLE games
+DROP COLUMN description;
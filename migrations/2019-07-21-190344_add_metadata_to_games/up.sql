@@ -0,0 +1,14 @@
+ALTER TABLE games
+ADD COLUMN IF NOT EXISTS
+description VARCHAR;
+ALTER TABLE games
+ADD COLUMN IF NOT EXISTS
+tags TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE games
+ADD COLUMN IF NOT EXISTS
+language VARCHAR;
+ALTER TABLE games
+ADD COLUMN IF NOT EXISTS
+difficulty VARCHAR;
+
+CREATE INDEX IF NOT EXISTS IX_GAME_TAGS ON games USING GIN (tags);
@@ -0,0 +1,6 @@
+DROP TABLE game_invitations;
+
+ALTER TABLE games
+DROP COLUMN share_token;
+ALTER TABLE games
+DROP COLUMN visibility;
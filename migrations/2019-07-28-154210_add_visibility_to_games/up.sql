@@ -0,0 +1,12 @@
+ALTER TABLE games
+ADD COLUMN IF NOT EXISTS
+visibility VARCHAR NOT NULL DEFAULT 'public';
+ALTER TABLE games
+ADD COLUMN IF NOT EXISTS
+share_token VARCHAR UNIQUE;
+
+CREATE TABLE game_invitations (
+    game_id INTEGER NOT NULL REFERENCES games(id),
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    PRIMARY KEY (game_id, user_id)
+);
@@ -0,0 +1,6 @@
+CREATE TABLE game_collaborators (
+    game_id INTEGER NOT NULL REFERENCES games(id),
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    role VARCHAR NOT NULL,
+    PRIMARY KEY (game_id, user_id)
+);
@@ -0,0 +1,2 @@
+DROP TABLE word_bank_shares;
+DROP TABLE word_banks;
@@ -0,0 +1,12 @@
+CREATE TABLE word_banks (
+    id SERIAL PRIMARY KEY,
+    owner_id INTEGER NOT NULL REFERENCES users(id),
+    name VARCHAR NOT NULL,
+    words TEXT[] NOT NULL
+);
+
+CREATE TABLE word_bank_shares (
+    word_bank_id INTEGER NOT NULL REFERENCES word_banks(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    PRIMARY KEY (word_bank_id, user_id)
+);
@@ -0,0 +1,4 @@
+ALTER TABLE game_participations
+DROP COLUMN outcome;
+ALTER TABLE games
+DROP COLUMN time_limit;
@@ -0,0 +1,11 @@
+-- Maximum play time of a participation in seconds, unlimited if NULL
+ALTER TABLE games
+ADD COLUMN IF NOT EXISTS
+time_limit INTEGER;
+
+-- How a participation ended, NULL while it is in progress
+ALTER TABLE game_participations
+ADD COLUMN IF NOT EXISTS
+outcome VARCHAR;
+
+UPDATE game_participations SET outcome = 'completed' WHERE end_time IS NOT NULL;
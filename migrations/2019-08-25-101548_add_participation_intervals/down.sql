@@ -0,0 +1 @@
+DROP TABLE participation_intervals;
@@ -0,0 +1,8 @@
+CREATE TABLE participation_intervals (
+    id SERIAL PRIMARY KEY,
+    participation_id INTEGER NOT NULL REFERENCES game_participations(id) ON DELETE CASCADE,
+    start_time TIMESTAMPTZ NOT NULL,
+    end_time TIMESTAMPTZ
+);
+
+CREATE INDEX IX_PARTICIPATION_INTERVALS ON participation_intervals (participation_id);
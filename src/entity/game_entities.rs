@@ -1,9 +1,75 @@
 #![allow(proc_macro_derive_resolution_fallback)]
-use super::super::schema::games;
-use chrono::{DateTime, Utc};
+use super::super::model::game_status::GameStatus;
+use super::super::schema::{daily_puzzles, game_collaborators, game_invitations, games};
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::sql_types::Text;
 
 pub type Date = DateTime<Utc>;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, GraphQLEnum, AsExpression, FromSqlRow)]
+#[sql_type = "Text"]
+pub enum GenerationStatus {
+    /// The puzzle is being generated in the background
+    Pending,
+    Succeeded,
+    /// See `generation_error` for the reason
+    Failed,
+}
+
+text_enum!(GenerationStatus {
+    Pending => "pending",
+    Succeeded => "succeeded",
+    Failed => "failed",
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, GraphQLEnum, AsExpression, FromSqlRow)]
+#[sql_type = "Text"]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+text_enum!(Difficulty {
+    Easy => "easy",
+    Medium => "medium",
+    Hard => "hard",
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, GraphQLEnum, AsExpression, FromSqlRow)]
+#[sql_type = "Text"]
+pub enum Visibility {
+    /// Listed and playable by everyone
+    Public,
+    /// Not listed, reachable with the share token of the game
+    Unlisted,
+    /// Only invited users can see and play the game
+    Private,
+}
+
+text_enum!(Visibility {
+    Public => "public",
+    Unlisted => "unlisted",
+    Private => "private",
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, GraphQLEnum, AsExpression, FromSqlRow)]
+#[sql_type = "Text"]
+pub enum ParticipationOutcome {
+    /// Every word was found
+    Completed,
+    /// The time limit of the game was exceeded
+    TimedOut,
+    /// The player gave up, the solutions were revealed to them
+    Forfeited,
+}
+
+text_enum!(ParticipationOutcome {
+    Completed => "completed",
+    TimedOut => "timed_out",
+    Forfeited => "forfeited",
+});
+
 #[derive(Queryable)]
 pub struct GameEntity {
     pub id: i32,
@@ -12,6 +78,34 @@ pub struct GameEntity {
     pub available_from: Option<Date>,
     pub available_to: Option<Date>,
     pub published: bool,
+    pub generation_status: GenerationStatus,
+    pub generation_error: Option<String>,
+    /// Archived games are hidden from the game list but their results stay readable
+    pub archived: bool,
+    /// The recurring schedule that created the game
+    pub schedule_id: Option<i32>,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    /// ISO 639-1 code of the language of the words
+    pub language: Option<String>,
+    pub difficulty: Option<Difficulty>,
+    pub visibility: Visibility,
+    /// Grants access to unlisted games
+    pub share_token: Option<String>,
+    /// Maximum play time of a participation in seconds
+    pub time_limit: Option<i32>,
+}
+
+impl GameEntity {
+    pub fn status(&self, now: Date) -> GameStatus {
+        GameStatus::new(
+            self.published,
+            self.archived,
+            self.available_from,
+            self.available_to,
+            now,
+        )
+    }
 }
 
 #[derive(Insertable)]
@@ -22,6 +116,13 @@ pub struct GameInsert {
     pub available_from: Option<Date>,
     pub available_to: Option<Date>,
     pub published: bool,
+    pub generation_status: GenerationStatus,
+    pub schedule_id: Option<i32>,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub language: Option<String>,
+    pub difficulty: Option<Difficulty>,
+    pub time_limit: Option<i32>,
 }
 
 #[derive(AsChangeset)]
@@ -30,4 +131,48 @@ pub struct GameUpdate {
     pub name: Option<String>,
     pub available_from: Option<Date>,
     pub available_to: Option<Date>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub language: Option<String>,
+    pub difficulty: Option<Difficulty>,
+    /// `Some(None)` removes the time limit
+    pub time_limit: Option<Option<i32>>,
+}
+
+#[derive(Queryable, Insertable)]
+#[table_name = "daily_puzzles"]
+pub struct DailyPuzzleEntity {
+    pub date: NaiveDate,
+    pub game_id: i32,
+    /// Theme of the dictionary the words were picked from
+    pub theme: String,
+}
+
+#[derive(Queryable, Insertable)]
+#[table_name = "game_invitations"]
+pub struct GameInvitationEntity {
+    pub game_id: i32,
+    pub user_id: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, GraphQLEnum, AsExpression, FromSqlRow)]
+#[sql_type = "Text"]
+pub enum CollaboratorRole {
+    /// Can edit drafts, regenerate puzzles and read the results
+    Editor,
+    /// Can read the participations and solutions
+    Viewer,
+}
+
+text_enum!(CollaboratorRole {
+    Editor => "editor",
+    Viewer => "viewer",
+});
+
+#[derive(Queryable, Insertable)]
+#[table_name = "game_collaborators"]
+pub struct GameCollaboratorEntity {
+    pub game_id: i32,
+    pub user_id: i32,
+    pub role: CollaboratorRole,
 }
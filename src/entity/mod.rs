@@ -1,3 +1,8 @@
 //! Hold representations of database entities
+#[macro_use]
+mod text_enum;
+
 pub mod game_entities;
 pub mod puzzle_entities;
+pub mod schedule_entities;
+pub mod word_bank_entities;
@@ -1,6 +1,7 @@
-use super::super::schema::puzzles;
+use super::super::schema::{puzzle_versions, puzzles};
 use crate::model::puzzle::Puzzle;
 use arrayvec::ArrayVec;
+use chrono::{DateTime, Utc};
 
 #[derive(Queryable)]
 pub struct PuzzleEntity {
@@ -10,6 +11,9 @@ pub struct PuzzleEntity {
     pub table_rows: i32,
     pub solutions: Vec<i32>,
     pub words: Vec<String>,
+    pub seed: Option<i64>,
+    /// The current version, `None` until the first puzzle is generated
+    pub version_id: Option<i32>,
 }
 
 #[derive(Insertable)]
@@ -21,6 +25,38 @@ pub struct PuzzleInsert<'a> {
     pub table_rows: i32,
     pub solutions: Vec<i32>,
     pub words: &'a Vec<String>,
+    pub seed: Option<i64>,
+    pub version_id: Option<i32>,
+}
+
+impl<'a> PuzzleInsert<'a> {
+    pub fn new(game_id: i32, version_id: i32, puzzle: &'a Puzzle) -> Self {
+        let (columns, rows) = puzzle.get_shape();
+        Self {
+            game_id: game_id,
+            game_table: puzzle.get_table().into_iter().collect(),
+            table_columns: columns as i32,
+            table_rows: rows as i32,
+            words: puzzle.get_words(),
+            solutions: flatten_solutions(puzzle),
+            seed: puzzle.get_seed().map(|s| s as i64),
+            version_id: Some(version_id),
+        }
+    }
+
+    /// Placeholder holding the words until the puzzle is generated
+    pub fn pending(game_id: i32, words: &'a Vec<String>) -> Self {
+        Self {
+            game_id: game_id,
+            game_table: String::new(),
+            table_columns: 0,
+            table_rows: 0,
+            words: words,
+            solutions: vec![],
+            seed: None,
+            version_id: None,
+        }
+    }
 }
 
 #[derive(AsChangeset)]
@@ -31,23 +67,92 @@ pub struct PuzzleUpdate {
     pub table_rows: i32,
     pub solutions: Vec<i32>,
     pub words: Option<Vec<String>>,
+    pub seed: Option<i64>,
+    pub version_id: Option<i32>,
 }
 
-impl From<Puzzle> for PuzzleUpdate {
-    fn from(puzzle: Puzzle) -> Self {
+impl PuzzleUpdate {
+    pub fn new(version_id: i32, puzzle: &Puzzle) -> Self {
         let (col, row) = puzzle.get_shape();
-        let solutions = puzzle
-            .get_solutions()
-            .into_iter()
-            .map(|(v1, v2)| [v1.x, v1.y, v2.x, v2.y].into())
-            .collect::<Vec<ArrayVec<_>>>();
 
         Self {
             game_table: puzzle.get_table().into_iter().collect(),
             table_columns: col as i32,
             table_rows: row as i32,
-            solutions: solutions.into_iter().flatten().collect(),
+            solutions: flatten_solutions(puzzle),
             words: Some(puzzle.get_words().clone()),
+            seed: puzzle.get_seed().map(|s| s as i64),
+            version_id: Some(version_id),
         }
     }
 }
+
+#[derive(Queryable)]
+pub struct PuzzleVersionEntity {
+    pub id: i32,
+    pub game_id: i32,
+    pub game_table: String,
+    pub table_columns: i32,
+    pub table_rows: i32,
+    pub solutions: Vec<i32>,
+    pub words: Vec<String>,
+    pub seed: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub version: i32,
+    pub created_by: i32,
+}
+
+#[derive(Insertable)]
+#[table_name = "puzzle_versions"]
+pub struct PuzzleVersionInsert<'a> {
+    pub game_id: i32,
+    pub game_table: String,
+    pub table_columns: i32,
+    pub table_rows: i32,
+    pub solutions: Vec<i32>,
+    pub words: &'a Vec<String>,
+    pub seed: Option<i64>,
+    pub version: i32,
+    pub created_by: i32,
+}
+
+impl<'a> PuzzleVersionInsert<'a> {
+    pub fn new(game_id: i32, version: i32, created_by: i32, puzzle: &'a Puzzle) -> Self {
+        let (columns, rows) = puzzle.get_shape();
+        Self {
+            game_id: game_id,
+            game_table: puzzle.get_table().into_iter().collect(),
+            table_columns: columns as i32,
+            table_rows: rows as i32,
+            words: puzzle.get_words(),
+            solutions: flatten_solutions(puzzle),
+            seed: puzzle.get_seed().map(|s| s as i64),
+            version: version,
+            created_by: created_by,
+        }
+    }
+}
+
+impl From<PuzzleVersionEntity> for PuzzleUpdate {
+    fn from(version: PuzzleVersionEntity) -> Self {
+        Self {
+            game_table: version.game_table,
+            table_columns: version.table_columns,
+            table_rows: version.table_rows,
+            solutions: version.solutions,
+            words: Some(version.words),
+            seed: version.seed,
+            version_id: Some(version.id),
+        }
+    }
+}
+
+/// Solutions are stored as a flat list of [x1, y1, x2, y2] quadruplets
+fn flatten_solutions(puzzle: &Puzzle) -> Vec<i32> {
+    let solutions = puzzle
+        .get_solutions()
+        .into_iter()
+        .map(|(v1, v2)| [v1.x, v1.y, v2.x, v2.y].into())
+        .collect::<Vec<ArrayVec<_>>>();
+    solutions.into_iter().flatten().collect()
+}
@@ -0,0 +1,85 @@
+use super::super::schema::game_schedules;
+use crate::model::schedule::Recurrence;
+use chrono::{DateTime, NaiveTime, Utc};
+use diesel::sql_types::Text;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, GraphQLEnum, AsExpression, FromSqlRow)]
+#[sql_type = "Text"]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+text_enum!(Weekday {
+    Monday => "monday",
+    Tuesday => "tuesday",
+    Wednesday => "wednesday",
+    Thursday => "thursday",
+    Friday => "friday",
+    Saturday => "saturday",
+    Sunday => "sunday",
+});
+
+impl From<Weekday> for chrono::Weekday {
+    fn from(day: Weekday) -> Self {
+        match day {
+            Weekday::Monday => chrono::Weekday::Mon,
+            Weekday::Tuesday => chrono::Weekday::Tue,
+            Weekday::Wednesday => chrono::Weekday::Wed,
+            Weekday::Thursday => chrono::Weekday::Thu,
+            Weekday::Friday => chrono::Weekday::Fri,
+            Weekday::Saturday => chrono::Weekday::Sat,
+            Weekday::Sunday => chrono::Weekday::Sun,
+        }
+    }
+}
+
+#[derive(Queryable)]
+pub struct GameScheduleEntity {
+    pub id: i32,
+    pub owner_id: i32,
+    pub name: String,
+    /// Pool of words the games are generated from
+    pub words: Vec<String>,
+    pub words_per_game: i32,
+    /// Name of a time zone in the tz database, e.g. `Europe/Budapest`
+    pub time_zone: String,
+    pub start_day: Weekday,
+    pub start_time: NaiveTime,
+    pub end_day: Weekday,
+    pub end_time: NaiveTime,
+    pub next_start: DateTime<Utc>,
+    pub active: bool,
+}
+
+impl GameScheduleEntity {
+    pub fn recurrence(&self) -> Result<Recurrence, String> {
+        Ok(Recurrence {
+            time_zone: self.time_zone.parse()?,
+            start_day: self.start_day.into(),
+            start_time: self.start_time,
+            end_day: self.end_day.into(),
+            end_time: self.end_time,
+        })
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "game_schedules"]
+pub struct GameScheduleInsert {
+    pub owner_id: i32,
+    pub name: String,
+    pub words: Vec<String>,
+    pub words_per_game: i32,
+    pub time_zone: String,
+    pub start_day: Weekday,
+    pub start_time: NaiveTime,
+    pub end_day: Weekday,
+    pub end_time: NaiveTime,
+    pub next_start: DateTime<Utc>,
+}
@@ -0,0 +1,31 @@
+use super::super::schema::{word_bank_shares, word_banks};
+
+#[derive(Queryable)]
+pub struct WordBankEntity {
+    pub id: i32,
+    pub owner_id: i32,
+    pub name: String,
+    pub words: Vec<String>,
+}
+
+#[derive(Insertable)]
+#[table_name = "word_banks"]
+pub struct WordBankInsert {
+    pub owner_id: i32,
+    pub name: String,
+    pub words: Vec<String>,
+}
+
+#[derive(AsChangeset)]
+#[table_name = "word_banks"]
+pub struct WordBankUpdate {
+    pub name: Option<String>,
+    pub words: Option<Vec<String>>,
+}
+
+#[derive(Queryable, Insertable)]
+#[table_name = "word_bank_shares"]
+pub struct WordBankShareEntity {
+    pub word_bank_id: i32,
+    pub user_id: i32,
+}
@@ -0,0 +1,218 @@
+use super::games::{validate_words, GameDTO};
+use super::puzzles::current_versions;
+use crate::entity::game_entities::{Difficulty, GameEntity, GameInsert, GenerationStatus};
+use crate::entity::puzzle_entities::PuzzleEntity;
+use crate::model::bundle::{anonymise, BundledGame, BundledResult, GameBundle};
+use crate::model::game_status::validate_window;
+use crate::model::interchange::{GameMetadata, PuzzleDocument};
+use crate::model::metadata::{normalise_language, normalise_tags, unique_name};
+use crate::model::participation::{validate_time_limit, GameParticipationEntity};
+use crate::model::puzzle::Puzzle;
+use crate::model::user::User;
+use crate::service::access::{load_game, Permission};
+use crate::service::generation::save_puzzle;
+use crate::DieselConnection;
+use chrono::Utc;
+use diesel::dsl::{insert_into, sql};
+use diesel::prelude::*;
+use diesel::sql_types::BigInt;
+use juniper::{FieldError, FieldResult};
+
+const MAX_BUNDLE_GAMES: usize = 50;
+
+/// Export the games with their puzzles as a JSON bundle, see `model::bundle`
+/// Requires user to be the owner or an editor of every game
+pub fn export_games(
+    connection: &DieselConnection,
+    current_user: &User,
+    game_ids: Vec<i32>,
+    include_results: bool,
+    anonymise_results: bool,
+) -> FieldResult<String> {
+    use crate::schema::puzzles::dsl as p;
+    use crate::schema::users::dsl as u;
+
+    if game_ids.is_empty() || game_ids.len() > MAX_BUNDLE_GAMES {
+        Err(format!(
+            "Between 1 and {} games can be exported at once",
+            MAX_BUNDLE_GAMES
+        ))?;
+    }
+
+    let mut games = Vec::with_capacity(game_ids.len());
+    for game_id in game_ids {
+        let game = load_game(connection, current_user, game_id, Permission::Edit)?;
+        if game.generation_status != GenerationStatus::Succeeded {
+            Err(format!(
+                "The puzzle of {} has not been generated",
+                game.name
+            ))?;
+        }
+        let puzzle = p::puzzles
+            .filter(p::game_id.eq(game_id))
+            .get_result::<PuzzleEntity>(connection)?;
+        let owner = u::users
+            .find(game.owner_id)
+            .select(u::name)
+            .get_result::<String>(connection)?;
+
+        let results = if include_results {
+            let mut results = fetch_results(connection, game_id)?;
+            if anonymise_results {
+                anonymise(&mut results);
+            }
+            Some(results)
+        } else {
+            None
+        };
+        let metadata = GameMetadata {
+            name: game.name,
+            owner: Some(owner),
+            available_from: game.available_from,
+            available_to: game.available_to,
+            description: game.description,
+            tags: game.tags,
+            language: game.language,
+            difficulty: game.difficulty.map(|d| d.as_str().to_string()),
+        };
+        games.push(BundledGame {
+            document: PuzzleDocument::new(&Puzzle::from(puzzle), Some(metadata)),
+            time_limit: game.time_limit,
+            results: results,
+        });
+    }
+
+    Ok(GameBundle::new(games, Utc::now()).to_json().to_string())
+}
+
+/// Create unpublished games of the current user from a bundle, all of them or none
+/// Games named like an existing game of the user are renamed to `<name> (imported)`
+/// Availability windows already over are dropped, results are not imported
+pub fn import_games(
+    connection: &DieselConnection,
+    current_user: &User,
+    bundle: String,
+) -> FieldResult<Vec<GameDTO>> {
+    use crate::schema::games::dsl as g;
+
+    let bundle = GameBundle::from_json(&bundle)?;
+    if bundle.games.len() > MAX_BUNDLE_GAMES {
+        Err(format!(
+            "At most {} games can be imported at once",
+            MAX_BUNDLE_GAMES
+        ))?;
+    }
+    let games = bundle
+        .games
+        .into_iter()
+        .map(|game| {
+            let puzzle = game.document.to_puzzle()?;
+            validate_words(puzzle.get_words())?;
+            let metadata = game
+                .document
+                .game
+                .clone()
+                .ok_or("The bundle contains a game without metadata")?;
+            Ok((game, metadata, puzzle))
+        })
+        .collect::<FieldResult<Vec<_>>>()?;
+
+    let now = Utc::now();
+    let result = connection.transaction::<_, FieldError, _>(|| {
+        let mut taken = g::games
+            .filter(g::owner_id.eq(current_user.id).and(g::archived.eq(false)))
+            .select(g::name)
+            .load::<String>(connection)?;
+
+        let mut result = Vec::with_capacity(games.len());
+        for (game, metadata, puzzle) in games {
+            let name = if taken.contains(&metadata.name) {
+                unique_name(&metadata.name, "imported", &taken)
+            } else {
+                metadata.name
+            };
+            taken.push(name.clone());
+            let (available_from, available_to) =
+                match validate_window(metadata.available_from, metadata.available_to, now) {
+                    Ok(()) => (metadata.available_from, metadata.available_to),
+                    Err(_) => (None, None),
+                };
+
+            let entity = insert_into(g::games)
+                .values(GameInsert {
+                    name: name,
+                    available_to: available_to,
+                    available_from: available_from,
+                    published: false,
+                    owner_id: current_user.id,
+                    generation_status: GenerationStatus::Succeeded,
+                    schedule_id: None,
+                    description: metadata.description,
+                    tags: normalise_tags(metadata.tags),
+                    language: metadata.language.and_then(|l| normalise_language(&l).ok()),
+                    difficulty: metadata
+                        .difficulty
+                        .and_then(|d| d.parse::<Difficulty>().ok()),
+                    time_limit: game
+                        .time_limit
+                        .filter(|l| validate_time_limit(Some(*l)).is_ok()),
+                })
+                .get_result::<GameEntity>(connection)?;
+            save_puzzle(connection, entity.id, current_user.id, &puzzle)?;
+
+            result.push(GameDTO::from_entity(
+                entity,
+                current_user.name.clone(),
+                true,
+            ));
+        }
+        Ok(result)
+    })?;
+
+    Ok(result)
+}
+
+/// Results of the current puzzle of the game, in the order the participations started
+fn fetch_results(connection: &DieselConnection, game_id: i32) -> QueryResult<Vec<BundledResult>> {
+    use crate::schema::game_participations::dsl as gp;
+    use crate::schema::solutions::dsl as s;
+    use crate::schema::users;
+
+    let participations = gp::game_participations
+        .filter(
+            gp::game_id
+                .eq(game_id)
+                .and(gp::puzzle_version_id.nullable().eq_any(current_versions())),
+        )
+        .inner_join(users::table)
+        .order_by(gp::start_time)
+        .load::<(GameParticipationEntity, User)>(connection)?;
+    let found = s::solutions
+        .filter(
+            s::game_id
+                .eq(game_id)
+                .and(s::puzzle_version_id.nullable().eq_any(current_versions())),
+        )
+        .group_by(s::user_id)
+        .select((s::user_id, sql::<BigInt>("COUNT(*)")))
+        .load::<(i32, i64)>(connection)?;
+
+    let result = participations
+        .into_iter()
+        .map(|(participation, user)| {
+            let found_words = found
+                .iter()
+                .find(|(user_id, _)| *user_id == user.id)
+                .map(|(_, count)| *count)
+                .unwrap_or(0);
+            BundledResult {
+                player: user.name,
+                start_time: participation.start_time,
+                end_time: participation.end_time,
+                duration: participation.duration,
+                found_words: found_words as i32,
+            }
+        })
+        .collect();
+    Ok(result)
+}
@@ -0,0 +1,91 @@
+use crate::entity::game_entities::{CollaboratorRole, GameCollaboratorEntity};
+use crate::model::user::{User, UserInfo};
+use crate::service::access::{load_game, Permission};
+use crate::DieselConnection;
+use diesel::dsl::{delete, insert_into};
+use diesel::pg::upsert::excluded;
+use diesel::prelude::*;
+use juniper::FieldResult;
+
+#[derive(GraphQLObject, Debug)]
+pub struct CollaboratorDTO {
+    pub user: UserInfo,
+    pub role: CollaboratorRole,
+}
+
+/// Get the collaborators of the game
+/// Requires user to be the owner
+pub fn fetch_collaborators(
+    connection: &DieselConnection,
+    current_user: &User,
+    game_id: i32,
+) -> FieldResult<Vec<CollaboratorDTO>> {
+    use crate::schema::game_collaborators::dsl as c;
+    use crate::schema::users;
+
+    load_game(connection, current_user, game_id, Permission::Own)?;
+    let result = c::game_collaborators
+        .filter(c::game_id.eq(game_id))
+        .inner_join(users::table)
+        .select((users::all_columns, c::role))
+        .order_by(users::dsl::name)
+        .load::<(User, CollaboratorRole)>(connection)?
+        .into_iter()
+        .map(|(user, role)| CollaboratorDTO {
+            user: UserInfo::from(user),
+            role: role,
+        })
+        .collect();
+    Ok(result)
+}
+
+/// Share the game with the user, or change the role of an existing collaborator
+pub fn add_collaborator(
+    connection: &DieselConnection,
+    current_user: &User,
+    game_id: i32,
+    user_id: i32,
+    role: CollaboratorRole,
+) -> FieldResult<bool> {
+    use crate::schema::game_collaborators::dsl as c;
+    use crate::schema::users::dsl as u;
+
+    let game = load_game(connection, current_user, game_id, Permission::Own)?;
+    if game.owner_id == user_id {
+        Err("The owner can not be a collaborator")?;
+    }
+    u::users
+        .find(user_id)
+        .select(u::id)
+        .get_result::<i32>(connection)
+        .optional()?
+        .ok_or("User not found")?;
+
+    insert_into(c::game_collaborators)
+        .values(GameCollaboratorEntity {
+            game_id: game_id,
+            user_id: user_id,
+            role: role,
+        })
+        .on_conflict((c::game_id, c::user_id))
+        .do_update()
+        .set(c::role.eq(excluded(c::role)))
+        .execute(connection)?;
+    Ok(true)
+}
+
+/// Stop sharing the game with the user
+pub fn remove_collaborator(
+    connection: &DieselConnection,
+    current_user: &User,
+    game_id: i32,
+    user_id: i32,
+) -> FieldResult<bool> {
+    use crate::schema::game_collaborators::dsl as c;
+
+    load_game(connection, current_user, game_id, Permission::Own)?;
+    let deleted =
+        delete(c::game_collaborators.filter(c::game_id.eq(game_id).and(c::user_id.eq(user_id))))
+            .execute(connection)?;
+    Ok(deleted > 0)
+}
@@ -0,0 +1,43 @@
+use super::games::GameDTO;
+use crate::entity::game_entities::{DailyPuzzleEntity, GameEntity};
+use crate::model::user::User;
+use crate::DieselConnection;
+use chrono::{NaiveDate, Utc};
+use diesel::prelude::*;
+use juniper::FieldResult;
+
+#[derive(GraphQLObject, Debug)]
+pub struct DailyPuzzleDTO {
+    pub date: NaiveDate,
+    pub theme: String,
+    pub game: GameDTO,
+}
+
+/// Get today's daily puzzle, `None` if it has not been created yet
+pub fn fetch_daily_puzzle(
+    connection: &DieselConnection,
+    current_user: &Option<User>,
+) -> FieldResult<Option<DailyPuzzleDTO>> {
+    use crate::schema::daily_puzzles::dsl as d;
+    use crate::schema::games;
+    use crate::schema::users;
+
+    let today = Utc::now().date().naive_utc();
+    let result = d::daily_puzzles
+        .find(today)
+        .inner_join(games::table.inner_join(users::table))
+        .get_result::<(DailyPuzzleEntity, (GameEntity, User))>(connection)
+        .optional()?
+        .map(|(daily, (game, owner))| {
+            let is_owner = current_user
+                .as_ref()
+                .map(|u| u.id == owner.id)
+                .unwrap_or(false);
+            DailyPuzzleDTO {
+                date: daily.date,
+                theme: daily.theme,
+                game: GameDTO::from_entity(game, owner.name, is_owner),
+            }
+        });
+    Ok(result)
+}
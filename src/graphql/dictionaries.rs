@@ -0,0 +1,109 @@
+use super::games::{self, GameDTO, GameSubmissionDTO};
+use crate::entity::game_entities::Difficulty;
+use crate::model::dictionary::{Dictionary, DICTIONARIES};
+use crate::model::extraction::extract_words;
+use crate::model::metadata::{escape_like, normalise_language, unique_name};
+use crate::model::user::User;
+use crate::service::generation::GenerationJobs;
+use crate::DieselConnection;
+use diesel::prelude::*;
+use juniper::FieldResult;
+
+const DEFAULT_LANGUAGE: &str = "en";
+const DEFAULT_WORD_COUNT: i32 = 10;
+/// Longest text accepted by `suggest_words`, in bytes
+const MAX_TEXT_LENGTH: usize = 20_000;
+
+/// A themed word list bundled with the server
+#[derive(GraphQLObject, Debug)]
+pub struct DictionaryDTO {
+    pub theme: String,
+    /// ISO 639-1 code of the language of the words
+    pub language: String,
+    pub word_count: i32,
+}
+
+pub fn fetch_dictionaries() -> Vec<DictionaryDTO> {
+    DICTIONARIES
+        .iter()
+        .map(|dictionary| DictionaryDTO {
+            theme: dictionary.theme.to_string(),
+            language: dictionary.language.to_string(),
+            word_count: dictionary.words().len() as i32,
+        })
+        .collect()
+}
+
+/// Create a new, unpublished game from random words of a dictionary, see `add_game`
+/// The difficulty decides the length of the words, medium if not given
+/// The game is named after the theme, e.g. `animals (random)`, `animals (random 2)`
+pub fn random_game(
+    connection: &DieselConnection,
+    current_user: &User,
+    jobs: &GenerationJobs,
+    theme: String,
+    language: Option<String>,
+    word_count: Option<i32>,
+    difficulty: Option<Difficulty>,
+) -> FieldResult<GameDTO> {
+    use crate::schema::games::dsl;
+
+    let language = language.unwrap_or_else(|| DEFAULT_LANGUAGE.to_string());
+    let dictionary = Dictionary::find(&theme, &language).ok_or("Dictionary not found")?;
+    let word_count = word_count.unwrap_or(DEFAULT_WORD_COUNT);
+    if word_count < 1 || word_count as usize > dictionary.words().len() {
+        Err("wordCount must be between 1 and the number of words of the dictionary")?;
+    }
+    let words = dictionary.pick_words_by_difficulty(
+        difficulty.unwrap_or(Difficulty::Medium),
+        word_count as usize,
+        &mut rand::thread_rng(),
+    );
+
+    let names = dsl::games
+        .filter(
+            dsl::owner_id
+                .eq(current_user.id)
+                .and(dsl::name.like(format!("{}%", escape_like(&theme)))),
+        )
+        .select(dsl::name)
+        .load::<String>(connection)?;
+
+    games::add_game(
+        connection,
+        current_user,
+        jobs,
+        GameSubmissionDTO {
+            name: unique_name(&theme, "random", &names),
+            words: words,
+            available_from: None,
+            available_to: None,
+            description: None,
+            tags: Some(vec![theme]),
+            language: Some(language),
+            difficulty: difficulty,
+            time_limit: None,
+        },
+    )
+}
+
+/// Suggest the words of a puzzle from free text, most frequent and longest words first
+/// The result can be used as the `words` of `GameSubmissionDTO`
+pub fn suggest_words(
+    text: String,
+    language: Option<String>,
+    word_count: Option<i32>,
+) -> FieldResult<Vec<String>> {
+    if text.len() > MAX_TEXT_LENGTH {
+        Err("The text is too long")?;
+    }
+    let language = language
+        .map(|l| normalise_language(&l))
+        .transpose()?
+        .unwrap_or_else(|| DEFAULT_LANGUAGE.to_string());
+    let word_count = word_count.unwrap_or(DEFAULT_WORD_COUNT);
+    if word_count < 1 {
+        Err("wordCount must be positive")?;
+    }
+    Ok(extract_words(&text, &language, word_count as usize)?)
+}
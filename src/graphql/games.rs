@@ -1,16 +1,29 @@
-use super::super::entity::{
-    game_entities::{GameEntity, GameInsert, GameUpdate},
-    puzzle_entities::PuzzleInsert,
+use super::super::entity::game_entities::{
+    Difficulty, GameEntity, GameInsert, GameUpdate, GenerationStatus, Visibility,
 };
-use super::super::model::{paginated::Paginated, puzzle, user::User, Date};
+use super::super::model::game_csv::parse_games;
+use super::super::model::game_status::{status_filter, validate_window, GameStatus};
+use super::super::model::metadata::{escape_like, normalise_language, normalise_tags, unique_name};
+use super::super::model::participation::validate_time_limit;
+use super::super::model::puzzle::same_words;
+use super::super::model::{interchange::PuzzleDocument, paginated::Paginated, user::User, Date};
 use super::super::schema;
+use super::super::service::access::{
+    check_access, generate_share_token, load_game, shared_games, Permission,
+};
+use super::super::service::generation::{insert_pending_game, save_puzzle, GenerationJobs};
 use super::super::service::pagination::*;
+use super::puzzles::current_versions;
 use super::*;
 use chrono::{DateTime, Utc};
-use diesel::dsl::{insert_into, update};
+use diesel::dsl::{delete, insert_into, sql, update, InnerJoin, IntoBoxed};
+use diesel::pg::Pg;
 use diesel::prelude::*;
 use diesel::result::Error as DieselError;
-use juniper::{self, FieldResult};
+use diesel::sql_types::BigInt;
+use juniper::{self, FieldError, FieldResult};
+
+const MAX_CSV_ROWS: usize = 200;
 
 #[derive(GraphQLObject, Debug)]
 pub struct GameDTO {
@@ -20,7 +33,63 @@ pub struct GameDTO {
     pub available_from: Date,
     pub available_to: Date,
     pub published: bool,
+    pub status: GameStatus,
     pub is_owner: bool,
+    pub generation_status: GenerationStatus,
+    /// Reason of the failure if `generation_status` is `FAILED`
+    pub generation_error: Option<String>,
+    pub archived: bool,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    /// ISO 639-1 code of the language of the words
+    pub language: Option<String>,
+    pub difficulty: Option<Difficulty>,
+    pub visibility: Visibility,
+    /// Token granting access to the game while it is unlisted, only shown to the owner
+    pub share_token: Option<String>,
+    /// Maximum play time of a participation in seconds
+    pub time_limit: Option<i32>,
+}
+
+impl GameDTO {
+    pub fn from_entity(game: GameEntity, owner: String, is_owner: bool) -> Self {
+        Self {
+            status: game.status(Utc::now()),
+            id: game.id,
+            name: game.name,
+            owner: owner,
+            available_from: game.available_from,
+            available_to: game.available_to,
+            published: game.published,
+            is_owner: is_owner,
+            generation_status: game.generation_status,
+            generation_error: game.generation_error,
+            archived: game.archived,
+            description: game.description,
+            tags: game.tags,
+            language: game.language,
+            difficulty: game.difficulty,
+            visibility: game.visibility,
+            share_token: if is_owner { game.share_token } else { None },
+            time_limit: game.time_limit,
+        }
+    }
+
+    /// The game as seen by the current user, who may be a collaborator rather than the owner
+    pub fn load_owner(
+        connection: &DieselConnection,
+        game: GameEntity,
+        current_user: &User,
+    ) -> QueryResult<Self> {
+        use self::schema::users::dsl::{name, users};
+
+        let owner = users
+            .find(game.owner_id)
+            .select(name)
+            .get_result::<String>(connection)?;
+        let is_owner = game.owner_id == current_user.id;
+        Ok(Self::from_entity(game, owner, is_owner))
+    }
 }
 
 pub type PaginatedGames = Paginated<GameDTO>;
@@ -39,12 +108,62 @@ graphql_object!(PaginatedGames: () |&self| {
     }
 });
 
+/// A game of the current user with statistics of its current puzzle
+#[derive(GraphQLObject, Debug)]
+pub struct MyGameDTO {
+    pub game: GameDTO,
+    pub participant_count: i32,
+    /// Participants who found every word
+    pub completion_count: i32,
+}
+
+pub type PaginatedMyGames = Paginated<MyGameDTO>;
+
+graphql_object!(PaginatedMyGames: () |&self| {
+    field items() -> &Vec<MyGameDTO> {
+        &self.items
+    }
+
+    field total_pages() -> i32 {
+        self.total_pages as i32
+    }
+
+    field page() -> i32 {
+        self.page as i32
+    }
+});
+
 #[derive(GraphQLInputObject, Debug)]
 pub struct GameSubmissionDTO {
     pub name: String,
     pub words: Vec<String>,
     pub available_from: Option<DateTime<Utc>>,
     pub available_to: Option<DateTime<Utc>>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+    /// ISO 639-1 code of the language of the words
+    pub language: Option<String>,
+    pub difficulty: Option<Difficulty>,
+    /// Maximum play time of a participation in seconds, participations exceeding it time out
+    pub time_limit: Option<i32>,
+}
+
+/// Outcome of a row of `add_games_from_csv`
+#[derive(GraphQLObject, Debug)]
+pub struct CsvRowReportDTO {
+    /// Line of the CSV the row starts on, counted from 1
+    pub row: i32,
+    pub name: Option<String>,
+    /// The created game, `None` unless every row is valid
+    pub game: Option<GameDTO>,
+    pub error: Option<String>,
+}
+
+#[derive(GraphQLObject, Debug)]
+pub struct CsvImportDTO {
+    /// Whether the games were created, only if every row is valid
+    pub created: bool,
+    pub rows: Vec<CsvRowReportDTO>,
 }
 
 #[derive(GraphQLInputObject, Debug)]
@@ -53,30 +172,132 @@ pub struct GameUpdateDTO {
     pub name: Option<String>,
     pub available_from: Option<DateTime<Utc>>,
     pub available_to: Option<DateTime<Utc>>,
+    /// Replace the words of the game, the puzzle is regenerated
+    pub words: Option<Vec<String>>,
+    pub description: Option<String>,
+    /// Replace the tags of the game
+    pub tags: Option<Vec<String>>,
+    pub language: Option<String>,
+    pub difficulty: Option<Difficulty>,
+    /// Maximum play time of a participation in seconds
+    pub time_limit: Option<i32>,
+    /// Remove the time limit of the game
+    pub clear_time_limit: Option<bool>,
+}
+
+#[derive(GraphQLInputObject, Debug, Default)]
+pub struct GameFilter {
+    /// Case insensitive search in the name of the game
+    pub search: Option<String>,
+    /// Name of the owner
+    pub owner: Option<String>,
+    /// Defaults to `LIVE` games and every game of the current user
+    /// `ARCHIVED` games are only listed by `myGames`
+    pub status: Option<Vec<GameStatus>>,
+    /// Games having every one of the tags
+    pub tags: Option<Vec<String>>,
+    pub difficulty: Option<Difficulty>,
+    pub language: Option<String>,
+}
+
+#[derive(GraphQLEnum, Debug, Clone, Copy, PartialEq)]
+pub enum GameSort {
+    /// Latest `availableFrom` first
+    Newest,
+    /// Earliest `availableTo` first, games without an end last
+    EndingSoon,
+    /// Most participations first
+    MostPlayed,
+}
+
+type GamesQuery = IntoBoxed<'static, InnerJoin<schema::games::table, schema::users::table>, Pg>;
+
+/// Apply the search, owner, status and metadata filters
+fn filter_games(query: GamesQuery, filter: &GameFilter) -> GamesQuery {
+    use self::schema::games::dsl::{difficulty, language, name, tags};
+    use self::schema::users::dsl as u;
+
+    let query = match filter.search.as_ref() {
+        Some(search) => query.filter(name.ilike(format!("%{}%", escape_like(search)))),
+        None => query,
+    };
+    let query = match filter.owner.as_ref() {
+        Some(owner) => query.filter(u::name.eq(owner.clone())),
+        None => query,
+    };
+    let query = match filter.status.as_ref() {
+        Some(status) => query.filter(status_filter(status, Utc::now())),
+        None => query,
+    };
+    let query = match filter.tags.as_ref() {
+        Some(wanted) => query.filter(tags.contains(normalise_tags(wanted.clone()))),
+        None => query,
+    };
+    let query = match filter.difficulty {
+        Some(wanted) => query.filter(difficulty.eq(wanted)),
+        None => query,
+    };
+    match filter.language.as_ref() {
+        Some(wanted) => query.filter(language.eq(wanted.trim().to_lowercase())),
+        None => query,
+    }
+}
+
+fn sort_games(query: GamesQuery, sort: GameSort) -> GamesQuery {
+    use self::schema::games::dsl::{available_from, available_to, id};
+
+    let query = match sort {
+        GameSort::Newest => query.order_by(available_from.desc().nulls_last()),
+        GameSort::EndingSoon => query.order_by(available_to.asc().nulls_last()),
+        GameSort::MostPlayed => query.order_by(
+            sql::<BigInt>(
+                "(SELECT COUNT(*) FROM game_participations gp WHERE gp.game_id = games.id)",
+            )
+            .desc(),
+        ),
+    };
+    query.then_order_by(id.desc())
 }
 
 pub fn fetch_games(
     connection: &DieselConnection,
     current_user: &Option<User>,
     page: Option<i32>,
+    filter: Option<GameFilter>,
+    sort: Option<GameSort>,
 ) -> FieldResult<PaginatedGames> {
-    use self::schema::games::dsl::{available_from, available_to, games, owner_id, published};
+    use self::schema::games::dsl::{archived, games, owner_id, visibility};
     use self::schema::users::dsl::users;
 
+    let filter = filter.unwrap_or_default();
+    if let Some(status) = filter.status.as_ref() {
+        if status.contains(&GameStatus::Archived) {
+            Err("Archived games are only listed by myGames")?;
+        }
+    }
+    let user_id = current_user.as_ref().map(|u| u.id).unwrap_or(-1);
+    let now = Utc::now();
+
     let page = page.unwrap_or(0) as i64;
     let query = games
         .inner_join(users)
-        .order_by(available_from.desc())
+        .filter(archived.eq(false))
         .into_boxed();
-    let is_avialable_query = published
-        .eq(true)
-        .and(available_from.le(Utc::now()))
-        .and(available_to.gt(Utc::now()).or(available_to.is_null()));
-    let query = if let Some(current_user) = &current_user {
-        query.filter(owner_id.eq(current_user.id).or(is_avialable_query))
+    // Other users' public games are listed once they started
+    let is_listed = status_filter(&[GameStatus::Live, GameStatus::Ended], now)
+        .and(visibility.eq(Visibility::Public));
+    let query = query.filter(owner_id.eq(user_id).or(is_listed));
+    let query = if filter.status.is_none() {
+        query.filter(
+            owner_id
+                .eq(user_id)
+                .or(status_filter(&[GameStatus::Live], now)),
+        )
     } else {
-        query.filter(is_avialable_query)
+        query
     };
+    let query = filter_games(query, &filter);
+    let query = sort_games(query, sort.unwrap_or(GameSort::Newest));
     let items = query
         .paginate(page)
         .per_page(25)
@@ -85,26 +306,86 @@ pub fn fetch_games(
         error!("Failed to read games {:?}", err);
         "Failed to read games"
     })?;
-    let user_id = current_user.as_ref().map(|u| u.id).unwrap_or(-1);
     let result = items
         .into_iter()
-        .map(|(game, user)| GameDTO {
-            id: game.id,
-            name: game.name,
-            owner: user.name,
-            available_from: game.available_from,
-            available_to: game.available_to,
-            published: game.published,
-            is_owner: user.id == user_id,
+        .map(|(game, user)| GameDTO::from_entity(game, user.name, user.id == user_id))
+        .collect();
+    Ok(Paginated::new(result, total_pages, page))
+}
+
+/// Every game of the current user and the games shared with them, archived ones included
+pub fn fetch_my_games(
+    connection: &DieselConnection,
+    current_user: &User,
+    page: Option<i32>,
+    filter: Option<GameFilter>,
+    sort: Option<GameSort>,
+) -> FieldResult<PaginatedMyGames> {
+    use self::schema::game_participations::dsl as gp;
+    use self::schema::games::dsl::{games, owner_id};
+    use self::schema::users::dsl::users;
+
+    let filter = filter.unwrap_or_default();
+    let page = page.unwrap_or(0) as i64;
+    let query = games
+        .inner_join(users)
+        .filter(
+            owner_id
+                .eq(current_user.id)
+                .or(schema::games::id.eq_any(shared_games(current_user.id))),
+        )
+        .into_boxed();
+    let query = filter_games(query, &filter);
+    let query = sort_games(query, sort.unwrap_or(GameSort::Newest));
+    let (items, total_pages) = query
+        .paginate(page)
+        .per_page(25)
+        .load_and_count_pages::<(GameEntity, User)>(connection)
+        .map_err(|err| {
+            error!("Failed to read games {:?}", err);
+            "Failed to read games"
+        })?;
+
+    let ids = items.iter().map(|(game, _)| game.id).collect::<Vec<_>>();
+    let counts = gp::game_participations
+        .filter(
+            gp::game_id
+                .eq_any(ids)
+                .and(gp::puzzle_version_id.nullable().eq_any(current_versions())),
+        )
+        .group_by(gp::game_id)
+        .select((
+            gp::game_id,
+            sql::<BigInt>("COUNT(*)"),
+            sql::<BigInt>("COUNT(*) FILTER (WHERE outcome = 'completed')"),
+        ))
+        .load::<(i32, i64, i64)>(connection)?;
+
+    let result = items
+        .into_iter()
+        .map(|(game, user)| {
+            let (participants, completions) = counts
+                .iter()
+                .find(|(id, _, _)| *id == game.id)
+                .map(|(_, participants, completions)| (*participants, *completions))
+                .unwrap_or((0, 0));
+            let is_owner = user.id == current_user.id;
+            MyGameDTO {
+                game: GameDTO::from_entity(game, user.name, is_owner),
+                participant_count: participants as i32,
+                completion_count: completions as i32,
+            }
         })
         .collect();
     Ok(Paginated::new(result, total_pages, page))
 }
 
+/// `share_token` grants access to unlisted games
 pub fn fetch_game_by_id(
     connection: &DieselConnection,
     current_user: &Option<User>,
     id: i32,
+    share_token: Option<String>,
 ) -> FieldResult<GameDTO> {
     use self::schema::games::dsl;
     use self::schema::users::dsl::users;
@@ -114,109 +395,259 @@ pub fn fetch_game_by_id(
         .inner_join(users)
         .order_by(dsl::available_from.desc())
         .into_boxed();
-    let is_avialable_query = dsl::published
-        .eq(true)
-        .and(dsl::available_from.le(Utc::now()))
-        .and(
-            dsl::available_to
-                .gt(Utc::now())
-                .or(dsl::available_to.is_null()),
-        );
+    let is_avialable_query = status_filter(&GameStatus::PUBLIC, Utc::now());
     let query = if let Some(current_user) = &current_user {
-        query.filter(dsl::owner_id.eq(current_user.id).or(is_avialable_query))
+        query.filter(
+            dsl::owner_id
+                .eq(current_user.id)
+                .or(dsl::id.eq_any(shared_games(current_user.id)))
+                .or(is_avialable_query),
+        )
     } else {
         query.filter(is_avialable_query)
     };
-    let result = query
+    let (game, user) = query
         .get_result::<(GameEntity, User)>(connection)
         .optional()
         .map_err(|e| {
             error!("Failed to read game {:?}", e);
             "Failed to read the game"
         })?
-        .map(|(game, user)| {
-            let user_id = user.id;
-            GameDTO {
-                id: game.id,
-                name: game.name,
-                owner: user.name,
-                available_from: game.available_from,
-                available_to: game.available_to,
-                published: game.published,
-                is_owner: current_user
-                    .as_ref()
-                    .map(|u| user_id == u.id)
-                    .unwrap_or(false),
-            }
-        })
         .ok_or("Game not found")?;
-    Ok(result)
+    check_access(
+        connection,
+        &game,
+        current_user.as_ref(),
+        share_token.as_deref(),
+    )?;
+    let is_owner = current_user
+        .as_ref()
+        .map(|u| user.id == u.id)
+        .unwrap_or(false);
+    Ok(GameDTO::from_entity(game, user.name, is_owner))
 }
 
+/// Create a new, unpublished game
+/// The puzzle is generated in the background, poll the game's `generation_status`
 pub fn add_game(
     connection: &DieselConnection,
     current_user: &User,
+    jobs: &GenerationJobs,
     game_submission: GameSubmissionDTO,
 ) -> FieldResult<GameDTO> {
-    use self::schema::games::dsl::games;
-    use self::schema::puzzles::dsl::puzzles;
+    let (game, words) = new_game(current_user, game_submission)?;
+    let game = insert_pending_game(connection, game, &words)?;
+    jobs.generate_puzzle(game.id, current_user.id, words);
 
-    game_submission.words.iter().try_for_each(|words| {
-        if words.len() < 3 {
-            Err("Words must be at least 3 characters long")
-        } else {
-            Ok(())
-        }
+    Ok(GameDTO::from_entity(game, current_user.name.clone(), true))
+}
+
+/// Create unpublished games from CSV, see `model::game_csv` for the format
+/// Either every game is created in one transaction or, if any row is invalid, none of them
+/// Names must be new to the user and to the other rows
+pub fn add_games_from_csv(
+    connection: &DieselConnection,
+    current_user: &User,
+    jobs: &GenerationJobs,
+    csv: String,
+) -> FieldResult<CsvImportDTO> {
+    use crate::schema::games::dsl;
+
+    let rows = parse_games(&csv)?;
+    if rows.len() > MAX_CSV_ROWS {
+        Err(format!(
+            "At most {} games can be created at once",
+            MAX_CSV_ROWS
+        ))?;
+    }
+    let existing = dsl::games
+        .filter(
+            dsl::owner_id
+                .eq(current_user.id)
+                .and(dsl::archived.eq(false)),
+        )
+        .select(dsl::name)
+        .load::<String>(connection)?;
+    let mut names = Vec::with_capacity(rows.len());
+    let rows = rows
+        .into_iter()
+        .map(|(line, row)| {
+            let row = row.and_then(|row| {
+                if existing.contains(&row.name) {
+                    return Err(format!("You already have a game named {}", row.name));
+                }
+                if names.contains(&row.name) {
+                    return Err(format!("An earlier row is named {} too", row.name));
+                }
+                names.push(row.name.clone());
+                let name = row.name.clone();
+                let submission = GameSubmissionDTO {
+                    name: row.name,
+                    words: row.words,
+                    available_from: row.available_from,
+                    available_to: row.available_to,
+                    description: None,
+                    tags: Some(row.tags),
+                    language: None,
+                    difficulty: None,
+                    time_limit: None,
+                };
+                new_game(current_user, submission)
+                    .map(|game| (name, game))
+                    .map_err(|e| e.to_string())
+            });
+            (line, row)
+        })
+        .collect::<Vec<_>>();
+
+    if rows.iter().any(|(_, row)| row.is_err()) {
+        let report = rows
+            .into_iter()
+            .map(|(line, row)| CsvRowReportDTO {
+                row: line as i32,
+                name: row.as_ref().ok().map(|(name, _)| name.clone()),
+                game: None,
+                error: row.err(),
+            })
+            .collect();
+        return Ok(CsvImportDTO {
+            created: false,
+            rows: report,
+        });
+    }
+
+    let games = connection.transaction::<_, DieselError, _>(|| {
+        rows.into_iter()
+            .map(|(line, row)| {
+                let (_, (game, words)) = row.expect("rows are valid");
+                insert_pending_game(connection, game, &words).map(|game| (line, game, words))
+            })
+            .collect::<Result<Vec<_>, _>>()
     })?;
 
+    let report = games
+        .into_iter()
+        .map(|(line, game, words)| {
+            jobs.generate_puzzle(game.id, current_user.id, words);
+            CsvRowReportDTO {
+                row: line as i32,
+                name: Some(game.name.clone()),
+                game: Some(GameDTO::from_entity(game, current_user.name.clone(), true)),
+                error: None,
+            }
+        })
+        .collect();
+    Ok(CsvImportDTO {
+        created: true,
+        rows: report,
+    })
+}
+
+/// Validate the submission of a new game
+fn new_game(
+    current_user: &User,
+    game_submission: GameSubmissionDTO,
+) -> Result<(GameInsert, Vec<String>), &'static str> {
+    validate_words(&game_submission.words)?;
+    validate_window(
+        game_submission.available_from,
+        game_submission.available_to,
+        Utc::now(),
+    )?;
+    validate_time_limit(game_submission.time_limit)?;
+
+    let GameSubmissionDTO {
+        name,
+        words,
+        available_from,
+        available_to,
+        description,
+        tags,
+        language,
+        difficulty,
+        time_limit,
+    } = game_submission;
+    let game = GameInsert {
+        name: name,
+        available_to: available_to,
+        available_from: available_from,
+        published: false,
+        owner_id: current_user.id,
+        generation_status: GenerationStatus::Pending,
+        schedule_id: None,
+        description: description,
+        tags: normalise_tags(tags.unwrap_or_default()),
+        language: language.map(|l| normalise_language(&l)).transpose()?,
+        difficulty: difficulty,
+        time_limit: time_limit,
+    };
+    Ok((game, words))
+}
+
+/// Create a new, unpublished game from a puzzle document
+/// See `model::interchange` for the format
+/// Unknown languages and difficulties are dropped, like in `import_games`
+pub fn import_game(
+    connection: &DieselConnection,
+    current_user: &User,
+    document: String,
+) -> FieldResult<GameDTO> {
+    use self::schema::games::dsl::games;
+
+    let document = PuzzleDocument::from_json(&document)?;
+    let puzzle = document.to_puzzle()?;
+    let game = document
+        .game
+        .ok_or("The document does not contain game metadata")?;
+
+    validate_words(puzzle.get_words())?;
+    validate_window(game.available_from, game.available_to, Utc::now())?;
+
     let result = connection.transaction::<_, DieselError, _>(|| {
-        let result = insert_into(games)
+        let game = insert_into(games)
             .values(GameInsert {
-                name: game_submission.name,
-                available_to: game_submission.available_to,
-                available_from: game_submission.available_from,
+                name: game.name,
+                available_to: game.available_to,
+                available_from: game.available_from,
                 published: false,
                 owner_id: current_user.id,
+                generation_status: GenerationStatus::Succeeded,
+                schedule_id: None,
+                description: game.description,
+                tags: normalise_tags(game.tags),
+                language: game.language.and_then(|l| normalise_language(&l).ok()),
+                difficulty: game.difficulty.and_then(|d| d.parse::<Difficulty>().ok()),
+                time_limit: None,
             })
-            .get_result::<GameEntity>(connection)
-            .map(|game| GameDTO {
-                id: game.id,
-                name: game.name,
-                owner: current_user.name.clone(),
-                available_from: game.available_from,
-                available_to: game.available_to,
-                published: game.published,
-                is_owner: true,
-            })?;
+            .get_result::<GameEntity>(connection)?;
 
-        let puzzle = puzzle::Puzzle::from_words(game_submission.words, 100).map_err(|e| {
-            error!("failed to generate puzzle, error: {:?}", e);
-            DieselError::RollbackTransaction
-        })?;
+        save_puzzle(connection, game.id, current_user.id, &puzzle)?;
 
-        let (columns, rows) = puzzle.get_shape();
-        insert_into(puzzles)
-            .values(PuzzleInsert {
-                game_id: result.id,
-                game_table: puzzle.get_table().into_iter().collect(),
-                table_columns: columns as i32,
-                table_rows: rows as i32,
-                words: puzzle.get_words(),
-                solutions: puzzle
-                    .get_solutions()
-                    .into_iter()
-                    .map(|(v1, v2)| vec![v1.x, v1.y, v2.x, v2.y].into_iter())
-                    .flatten()
-                    .collect(),
-            })
-            .execute(connection)?;
-
-        Ok(result)
+        Ok(game)
     })?;
 
-    Ok(result)
+    Ok(GameDTO::from_entity(
+        result,
+        current_user.name.clone(),
+        true,
+    ))
 }
 
+pub fn validate_words(words: &[String]) -> Result<(), &'static str> {
+    if words.is_empty() {
+        return Err("Games must have at least one word");
+    }
+    words.iter().try_for_each(|word| {
+        if word.len() < 3 {
+            Err("Words must be at least 3 characters long")
+        } else {
+            Ok(())
+        }
+    })
+}
+
+/// Publish a game
+/// Games without `available_from` become available immediately
 pub fn publish_game(
     connection: &DieselConnection,
     current_user: &User,
@@ -224,47 +655,328 @@ pub fn publish_game(
 ) -> FieldResult<bool> {
     use self::schema::games::dsl;
 
-    update(dsl::games.filter(dsl::owner_id.eq(current_user.id).and(dsl::id.eq(game_id))))
-        .set(dsl::published.eq(true))
+    let query = dsl::games.filter(dsl::owner_id.eq(current_user.id).and(dsl::id.eq(game_id)));
+    let game = query
+        .get_result::<GameEntity>(connection)
+        .optional()?
+        .ok_or("Game not found")?;
+
+    let now = Utc::now();
+    let available_from = game.available_from.unwrap_or(now);
+    validate_window(Some(available_from), game.available_to, now)?;
+
+    update(query)
+        .set((
+            dsl::published.eq(true),
+            dsl::available_from.eq(available_from),
+        ))
         .execute(connection)?;
 
     Ok(true)
 }
 
+/// Revert a published game to a draft
+/// Refused once anyone has participated in the game
+pub fn unpublish_game(
+    connection: &DieselConnection,
+    current_user: &User,
+    game_id: i32,
+) -> FieldResult<GameDTO> {
+    use crate::schema::game_participations;
+    use crate::schema::games::dsl;
+    use diesel::dsl::exists;
+    use diesel::select;
+
+    connection.transaction::<_, FieldError, _>(|| {
+        let game = dsl::games
+            .filter(dsl::id.eq(game_id).and(dsl::owner_id.eq(current_user.id)))
+            .get_result::<GameEntity>(connection)
+            .optional()?
+            .ok_or("Game not found")?;
+
+        if !game.published {
+            Err("The game is not published")?;
+        }
+        if game.archived {
+            Err("Archived games can not be unpublished")?;
+        }
+        let has_participants = select(exists(
+            game_participations::table.filter(game_participations::dsl::game_id.eq(game_id)),
+        ))
+        .get_result::<bool>(connection)?;
+        if has_participants {
+            Err("The game already has participants, archive it instead")?;
+        }
+
+        let game = update(dsl::games.filter(dsl::id.eq(game_id)))
+            .set(dsl::published.eq(false))
+            .get_result::<GameEntity>(connection)?;
+
+        Ok(GameDTO::from_entity(game, current_user.name.clone(), true))
+    })
+}
+
+/// Clone a game's name, words and availability into a new draft
+/// The copy gets a freshly generated puzzle, an availability window already over is dropped
+pub fn duplicate_game(
+    connection: &DieselConnection,
+    current_user: &User,
+    jobs: &GenerationJobs,
+    game_id: i32,
+) -> FieldResult<GameDTO> {
+    use crate::schema::games::{self, dsl};
+    use crate::schema::puzzles;
+
+    let (game, words) = dsl::games
+        .inner_join(puzzles::table)
+        .filter(dsl::id.eq(game_id).and(dsl::owner_id.eq(current_user.id)))
+        .select((games::all_columns, puzzles::dsl::words))
+        .get_result::<(GameEntity, Vec<String>)>(connection)
+        .optional()?
+        .ok_or("Game not found")?;
+
+    let names = dsl::games
+        .filter(
+            dsl::owner_id
+                .eq(current_user.id)
+                .and(dsl::name.like(format!("{}%", escape_like(&game.name)))),
+        )
+        .select(dsl::name)
+        .load::<String>(connection)?;
+    let name = unique_name(&game.name, "copy", &names);
+    // A window already over would keep the copy from being published
+    let (available_from, available_to) =
+        match validate_window(game.available_from, game.available_to, Utc::now()) {
+            Ok(()) => (game.available_from, game.available_to),
+            Err(_) => (None, None),
+        };
+
+    let game = GameInsert {
+        name: name,
+        available_to: available_to,
+        available_from: available_from,
+        published: false,
+        owner_id: current_user.id,
+        generation_status: GenerationStatus::Pending,
+        schedule_id: None,
+        description: game.description,
+        tags: game.tags,
+        language: game.language,
+        difficulty: game.difficulty,
+        time_limit: game.time_limit,
+    };
+    let game = insert_pending_game(connection, game, &words)?;
+    jobs.generate_puzzle(game.id, current_user.id, words);
+
+    Ok(GameDTO::from_entity(game, current_user.name.clone(), true))
+}
+
+/// Update an unpublished game
+/// If new words are given the puzzle is regenerated in the background
+/// Requires user to be the owner or an editor
 pub fn update_game(
     connection: &DieselConnection,
     current_user: &User,
+    jobs: &GenerationJobs,
     changeset: GameUpdateDTO,
 ) -> FieldResult<GameDTO> {
     use crate::schema::games as g;
 
+    if let Some(words) = changeset.words.as_ref() {
+        validate_words(words)?;
+    }
+    validate_time_limit(changeset.time_limit)?;
+    let clear_time_limit = changeset.clear_time_limit.unwrap_or(false);
+    if clear_time_limit && changeset.time_limit.is_some() {
+        Err("timeLimit and clearTimeLimit can not be set together")?;
+    }
+
     let game_id = changeset.game_id;
+    let words = changeset.words;
+    let has_changes = changeset.name.is_some()
+        || changeset.available_to.is_some()
+        || changeset.available_from.is_some()
+        || changeset.description.is_some()
+        || changeset.tags.is_some()
+        || changeset.language.is_some()
+        || changeset.difficulty.is_some()
+        || changeset.time_limit.is_some()
+        || clear_time_limit;
     let changeset = GameUpdate {
         name: changeset.name,
         available_to: changeset.available_to,
         available_from: changeset.available_from,
+        description: changeset.description,
+        tags: changeset.tags.map(normalise_tags),
+        language: changeset
+            .language
+            .map(|l| normalise_language(&l))
+            .transpose()?,
+        difficulty: changeset.difficulty,
+        time_limit: if clear_time_limit {
+            Some(None)
+        } else {
+            changeset.time_limit.map(Some)
+        },
     };
 
-    let game = update(
-        g::table.filter(
-            g::dsl::id
-                .eq(game_id)
-                .and(g::dsl::owner_id.eq(current_user.id))
-                .and(g::dsl::published.eq(false)),
-        ),
-    )
-    .set(changeset)
-    .get_result::<GameEntity>(connection)?;
-
-    let result = GameDTO {
-        id: game.id,
-        name: game.name,
-        owner: current_user.name.clone(),
-        available_from: game.available_from,
-        available_to: game.available_to,
-        published: game.published,
-        is_owner: true,
+    let (game, words) = connection.transaction::<_, FieldError, _>(|| {
+        use crate::schema::puzzles as p;
+
+        let game = load_game(connection, current_user, game_id, Permission::Edit)?;
+        if game.published {
+            Err("Published games can not be updated")?;
+        }
+        let query = g::table.filter(g::dsl::id.eq(game_id));
+
+        // A window already over is only refused when it is changed, or on publish
+        if changeset.available_from.is_some() || changeset.available_to.is_some() {
+            validate_window(
+                changeset.available_from.or(game.available_from),
+                changeset.available_to.or(game.available_to),
+                Utc::now(),
+            )?;
+        }
+
+        if game.generation_status == GenerationStatus::Pending && words.is_some() {
+            Err("The puzzle is being generated")?;
+        }
+        let current_words = p::table
+            .find(game_id)
+            .select(p::dsl::words)
+            .get_result::<Vec<String>>(connection)?;
+        // The puzzle is regenerated in the background once the transaction is committed
+        let words = words.filter(|w| !same_words(&current_words, w));
+        if let Some(words) = words.as_ref() {
+            update(p::table.find(game_id))
+                .set(p::dsl::words.eq(words))
+                .execute(connection)?;
+            update(query)
+                .set((
+                    g::dsl::generation_status.eq(GenerationStatus::Pending),
+                    g::dsl::generation_error.eq(None::<String>),
+                ))
+                .execute(connection)?;
+        }
+
+        let game = if has_changes {
+            update(query)
+                .set(changeset)
+                .get_result::<GameEntity>(connection)?
+        } else {
+            query.get_result::<GameEntity>(connection)?
+        };
+        Ok((game, words))
+    })?;
+
+    if let Some(words) = words {
+        jobs.generate_puzzle(game_id, current_user.id, words);
+    }
+
+    Ok(GameDTO::load_owner(connection, game, current_user)?)
+}
+
+/// Delete an unpublished game together with its puzzle and every version of it
+pub fn delete_game(
+    connection: &DieselConnection,
+    current_user: &User,
+    game_id: i32,
+) -> FieldResult<bool> {
+    use crate::schema::{
+        game_collaborators, game_invitations, game_participations, games, puzzle_versions, puzzles,
+        solutions,
+    };
+
+    connection.transaction::<_, FieldError, _>(|| {
+        let game = games::table
+            .filter(
+                games::dsl::id
+                    .eq(game_id)
+                    .and(games::dsl::owner_id.eq(current_user.id)),
+            )
+            .get_result::<GameEntity>(connection)
+            .optional()?
+            .ok_or("Game not found")?;
+
+        if game.published {
+            Err("Published games can not be deleted, archive them instead")?;
+        }
+        if game.generation_status == GenerationStatus::Pending {
+            Err("The puzzle is being generated")?;
+        }
+
+        delete(solutions::table.filter(solutions::dsl::game_id.eq(game_id))).execute(connection)?;
+        delete(game_participations::table.filter(game_participations::dsl::game_id.eq(game_id)))
+            .execute(connection)?;
+        delete(puzzles::table.filter(puzzles::dsl::game_id.eq(game_id))).execute(connection)?;
+        delete(puzzle_versions::table.filter(puzzle_versions::dsl::game_id.eq(game_id)))
+            .execute(connection)?;
+        delete(game_invitations::table.filter(game_invitations::dsl::game_id.eq(game_id)))
+            .execute(connection)?;
+        delete(game_collaborators::table.filter(game_collaborators::dsl::game_id.eq(game_id)))
+            .execute(connection)?;
+        delete(games::table.filter(games::dsl::id.eq(game_id))).execute(connection)?;
+
+        Ok(true)
+    })
+}
+
+/// Archive a published game
+/// Archived games are hidden from the game list and accept no new solutions,
+/// but their participations and results stay readable
+pub fn archive_game(
+    connection: &DieselConnection,
+    current_user: &User,
+    game_id: i32,
+) -> FieldResult<GameDTO> {
+    use crate::schema::games::dsl;
+
+    let game = dsl::games
+        .filter(dsl::id.eq(game_id).and(dsl::owner_id.eq(current_user.id)))
+        .get_result::<GameEntity>(connection)
+        .optional()?
+        .ok_or("Game not found")?;
+
+    if !game.published {
+        Err("Unpublished games can not be archived, delete them instead")?;
+    }
+
+    let game = update(dsl::games.filter(dsl::id.eq(game_id)))
+        .set(dsl::archived.eq(true))
+        .get_result::<GameEntity>(connection)?;
+
+    Ok(GameDTO::from_entity(game, current_user.name.clone(), true))
+}
+
+/// Change who can see and play the game
+/// Unlisted games get a share token, `reset_share_token` replaces the existing one
+/// Other visibilities drop the token, so old links stay dead if the game is unlisted again
+pub fn set_game_visibility(
+    connection: &DieselConnection,
+    current_user: &User,
+    game_id: i32,
+    visibility: Visibility,
+    reset_share_token: bool,
+) -> FieldResult<GameDTO> {
+    use crate::schema::games::dsl;
+
+    let query = dsl::games.filter(dsl::id.eq(game_id).and(dsl::owner_id.eq(current_user.id)));
+    let game = query
+        .get_result::<GameEntity>(connection)
+        .optional()?
+        .ok_or("Game not found")?;
+
+    let share_token = match game.share_token {
+        _ if visibility != Visibility::Unlisted => None,
+        Some(token) if !reset_share_token => Some(token),
+        _ => Some(generate_share_token()),
     };
+    let game = update(query)
+        .set((
+            dsl::visibility.eq(visibility),
+            dsl::share_token.eq(share_token),
+        ))
+        .get_result::<GameEntity>(connection)?;
 
-    Ok(result)
+    Ok(GameDTO::from_entity(game, current_user.name.clone(), true))
 }
@@ -0,0 +1,75 @@
+use crate::entity::game_entities::GameInvitationEntity;
+use crate::model::user::{User, UserInfo};
+use crate::service::access::{load_game, Permission};
+use crate::DieselConnection;
+use diesel::dsl::{delete, insert_into};
+use diesel::prelude::*;
+use juniper::FieldResult;
+
+/// Get the users invited to the game
+/// Requires user to be the owner
+pub fn fetch_invitations(
+    connection: &DieselConnection,
+    current_user: &User,
+    game_id: i32,
+) -> FieldResult<Vec<UserInfo>> {
+    use crate::schema::game_invitations::dsl as i;
+    use crate::schema::users;
+
+    load_game(connection, current_user, game_id, Permission::Own)?;
+    let result = i::game_invitations
+        .filter(i::game_id.eq(game_id))
+        .inner_join(users::table)
+        .select(users::all_columns)
+        .order_by(users::dsl::name)
+        .load::<User>(connection)?
+        .into_iter()
+        .map(UserInfo::from)
+        .collect();
+    Ok(result)
+}
+
+/// Allow the user to see and play the private game
+pub fn invite_user(
+    connection: &DieselConnection,
+    current_user: &User,
+    game_id: i32,
+    user_id: i32,
+) -> FieldResult<bool> {
+    use crate::schema::game_invitations::dsl as i;
+    use crate::schema::users::dsl as u;
+
+    load_game(connection, current_user, game_id, Permission::Own)?;
+    u::users
+        .find(user_id)
+        .select(u::id)
+        .get_result::<i32>(connection)
+        .optional()?
+        .ok_or("User not found")?;
+
+    insert_into(i::game_invitations)
+        .values(GameInvitationEntity {
+            game_id: game_id,
+            user_id: user_id,
+        })
+        .on_conflict_do_nothing()
+        .execute(connection)?;
+    Ok(true)
+}
+
+/// Revoke the invitation of the user
+/// Existing participations and results of the user are kept
+pub fn uninvite_user(
+    connection: &DieselConnection,
+    current_user: &User,
+    game_id: i32,
+    user_id: i32,
+) -> FieldResult<bool> {
+    use crate::schema::game_invitations::dsl as i;
+
+    load_game(connection, current_user, game_id, Permission::Own)?;
+    let deleted =
+        delete(i::game_invitations.filter(i::game_id.eq(game_id).and(i::user_id.eq(user_id))))
+            .execute(connection)?;
+    Ok(deleted > 0)
+}
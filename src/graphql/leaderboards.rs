@@ -0,0 +1,203 @@
+use super::puzzles::current_versions;
+use crate::entity::game_entities::{GameEntity, ParticipationOutcome, Visibility};
+use crate::model::game_status::GameStatus;
+use crate::model::leaderboard::validate_paging;
+use crate::model::user::{User, UserInfo};
+use crate::service::access::check_access;
+use crate::service::pagination::{FindRow, FoundRow, Paginate, Paginated};
+use crate::DieselConnection;
+use chrono::Utc;
+use diesel::dsl::sql;
+use diesel::prelude::*;
+use diesel::query_dsl::methods::LoadQuery;
+use diesel::sql_types::{BigInt, Bool, Double};
+use juniper::FieldResult;
+
+#[derive(GraphQLObject, Debug)]
+pub struct LeaderboardEntryDTO {
+    pub rank: i32,
+    /// Other players have the same result and rank
+    pub tied: bool,
+    /// Percentage of the ranked players with the same or a worse result
+    pub percentile: f64,
+    pub user: UserInfo,
+    /// 1 on the leaderboard of a game
+    pub completed_games: i32,
+    /// Time spent on the completed games in milliseconds
+    pub duration: f64,
+}
+
+#[derive(GraphQLObject, Debug)]
+pub struct LeaderboardDTO {
+    pub entries: Vec<LeaderboardEntryDTO>,
+    pub total_pages: i32,
+    pub page: i32,
+    /// The entry of the current user wherever it is, `None` if the user is not ranked
+    pub own_entry: Option<LeaderboardEntryDTO>,
+}
+
+/// The player, completed games, total duration, rank, tied and percentile
+/// Ranked in SQL, tied results share the rank and the next result skips the tied places
+type Standing = (User, i64, i64, i64, bool, f64);
+
+/// Rank the players who completed the current puzzle of the game, the fastest first
+/// `share_token` grants access to unlisted games
+pub fn fetch_game_leaderboard(
+    connection: &DieselConnection,
+    current_user: &Option<User>,
+    game_id: i32,
+    share_token: Option<String>,
+    page: Option<i32>,
+    per_page: Option<i32>,
+) -> FieldResult<LeaderboardDTO> {
+    use crate::schema::game_participations::dsl as gp;
+    use crate::schema::games::dsl::games;
+    use crate::schema::users;
+
+    let (page, per_page) = validate_paging(page, per_page)?;
+    let game = games
+        .find(game_id)
+        .get_result::<GameEntity>(connection)
+        .optional()?
+        .ok_or("Game not found")?;
+    check_access(
+        connection,
+        &game,
+        current_user.as_ref(),
+        share_token.as_deref(),
+    )?;
+    // Drafts and scheduled games are not open for play yet
+    match game.status(Utc::now()) {
+        GameStatus::Draft | GameStatus::Scheduled => Err("Game not available")?,
+        GameStatus::Live | GameStatus::Ended | GameStatus::Archived => (),
+    }
+
+    let standings = || {
+        gp::game_participations
+            .filter(
+                gp::game_id
+                    .eq(game_id)
+                    .and(gp::outcome.eq(ParticipationOutcome::Completed))
+                    .and(gp::duration.is_not_null())
+                    .and(gp::puzzle_version_id.nullable().eq_any(current_versions())),
+            )
+            .inner_join(users::table)
+            .select((
+                users::all_columns,
+                sql::<BigInt>("1::BIGINT"),
+                sql::<BigInt>("game_participations.duration::BIGINT"),
+                sql::<BigInt>("RANK() OVER (ORDER BY game_participations.duration)"),
+                sql::<Bool>("COUNT(*) OVER (PARTITION BY game_participations.duration) > 1"),
+                sql::<Double>(
+                    "CUME_DIST() OVER (ORDER BY game_participations.duration DESC) * 100",
+                ),
+            ))
+            .order_by((gp::duration, gp::end_time))
+    };
+    Ok(leaderboard(
+        connection,
+        standings,
+        current_user,
+        page,
+        per_page,
+    )?)
+}
+
+/// Rank the players over the public games, the most completed games first,
+/// then the shortest total time
+pub fn fetch_global_leaderboard(
+    connection: &DieselConnection,
+    current_user: &Option<User>,
+    page: Option<i32>,
+    per_page: Option<i32>,
+) -> FieldResult<LeaderboardDTO> {
+    use crate::schema::game_participations::dsl as gp;
+    use crate::schema::games::dsl as g;
+    use crate::schema::users::{self, dsl as u};
+
+    let (page, per_page) = validate_paging(page, per_page)?;
+    let standings = || {
+        gp::game_participations
+            .inner_join(g::games)
+            .inner_join(u::users)
+            .filter(
+                gp::outcome
+                    .eq(ParticipationOutcome::Completed)
+                    .and(gp::duration.is_not_null())
+                    .and(g::visibility.eq(Visibility::Public))
+                    .and(gp::puzzle_version_id.nullable().eq_any(current_versions())),
+            )
+            .group_by(u::id)
+            .select((
+                users::all_columns,
+                sql::<BigInt>("COUNT(*)"),
+                sql::<BigInt>("SUM(game_participations.duration)"),
+                sql::<BigInt>(
+                    "RANK() OVER (ORDER BY COUNT(*) DESC, SUM(game_participations.duration))",
+                ),
+                sql::<Bool>(
+                    "COUNT(*) OVER (PARTITION BY COUNT(*), SUM(game_participations.duration)) > 1",
+                ),
+                sql::<Double>(
+                    "CUME_DIST() OVER (ORDER BY COUNT(*), SUM(game_participations.duration) DESC) \
+                     * 100",
+                ),
+            ))
+            .order_by((
+                sql::<BigInt>("COUNT(*)").desc(),
+                sql::<BigInt>("SUM(game_participations.duration)"),
+                u::id,
+            ))
+    };
+    Ok(leaderboard(
+        connection,
+        standings,
+        current_user,
+        page,
+        per_page,
+    )?)
+}
+
+/// The page of the ranked standings, the entry of the current user is read on its own
+fn leaderboard<Q, F>(
+    connection: &DieselConnection,
+    standings: F,
+    current_user: &Option<User>,
+    page: usize,
+    per_page: usize,
+) -> QueryResult<LeaderboardDTO>
+where
+    F: Fn() -> Q,
+    Paginated<Q>: LoadQuery<PgConnection, (Standing, i64)>,
+    FoundRow<Q>: LoadQuery<PgConnection, Standing>,
+{
+    let (entries, total_pages) = standings()
+        .paginate(page as i64)
+        .per_page(per_page as i64)
+        .load_and_count_pages::<Standing>(connection)?;
+    let own_entry = match current_user {
+        Some(user) => standings()
+            .find_row(user.id)
+            .get_result::<Standing>(connection)
+            .optional()?,
+        None => None,
+    };
+    Ok(LeaderboardDTO {
+        entries: entries.into_iter().map(to_dto).collect(),
+        total_pages: total_pages as i32,
+        page: page as i32,
+        own_entry: own_entry.map(to_dto),
+    })
+}
+
+fn to_dto(standing: Standing) -> LeaderboardEntryDTO {
+    let (user, completed_games, duration, rank, tied, percentile) = standing;
+    LeaderboardEntryDTO {
+        rank: rank as i32,
+        tied: tied,
+        percentile: percentile,
+        user: UserInfo::from(user),
+        completed_games: completed_games as i32,
+        duration: duration as f64,
+    }
+}
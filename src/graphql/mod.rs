@@ -1,19 +1,29 @@
+pub mod bundles;
+pub mod collaborators;
+pub mod daily;
+pub mod dictionaries;
 pub mod games;
+pub mod invitations;
+pub mod leaderboards;
 pub mod mutation;
 pub mod participations;
 pub mod puzzles;
 pub mod query;
+pub mod schedules;
 pub mod solutions;
+pub mod word_banks;
 
 pub use self::mutation::Mutation;
 pub use self::query::Query;
 use super::model::user::User;
+use crate::service::generation::GenerationJobs;
 use crate::DieselConnection;
 use juniper::RootNode;
 
 pub struct Context {
     pub connection: *const DieselConnection,
     pub user: Option<User>,
+    pub jobs: GenerationJobs,
 }
 
 impl juniper::Context for Context {}
@@ -1,4 +1,5 @@
 use super::*;
+use crate::entity::game_entities::{CollaboratorRole, Difficulty, Visibility};
 use crate::model::solution::SolutionDTO;
 use crate::model::vector::Vector;
 use juniper::{self, FieldResult};
@@ -6,6 +7,8 @@ use juniper::{self, FieldResult};
 pub struct Mutation {}
 
 graphql_object!( Mutation: Context as "Mutation" | &self | {
+    /// Create a new, unpublished game
+    /// The puzzle is generated in the background, poll the game's `generationStatus` for the result
     field add_game(
         &executor,
         submission: games::GameSubmissionDTO
@@ -16,13 +19,70 @@ graphql_object!( Mutation: Context as "Mutation" | &self | {
         let connection = unsafe {
             &*connection
         };
-        games::add_game(connection, &user, submission)
+        games::add_game(connection, &user, &context.jobs, submission)
+    }
+
+    /// Create a new, unpublished game from random words of a bundled dictionary
+    /// `language` defaults to `en`, `wordCount` to 10
+    field random_game(
+        &executor,
+        theme: String,
+        language: Option<String>,
+        word_count: Option<i32>,
+        difficulty: Option<Difficulty>,
+    ) -> FieldResult<games::GameDTO> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        dictionaries::random_game(connection, user, &context.jobs, theme, language, word_count, difficulty)
+    }
+
+    /// Create unpublished games from a bundle of `exportGames`, all of them or none
+    /// Games named like an existing game are renamed to `<name> (imported)`
+    field import_games(&executor, bundle: String) -> FieldResult<Vec<games::GameDTO>> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        bundles::import_games(connection, user, bundle)
+    }
+
+    /// Create unpublished games from CSV with the columns
+    /// `name`, `words`, `available_from`, `available_to` and `tags`
+    /// Words and tags are separated by `;`, dates are in RFC 3339 format
+    /// Nothing is created if any row is invalid, see the report of the rows
+    field add_games_from_csv(&executor, csv: String) -> FieldResult<games::CsvImportDTO> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        games::add_games_from_csv(connection, user, &context.jobs, csv)
+    }
+
+    /// Create a new, unpublished game from an exported puzzle document
+    field import_game(&executor, document: String) -> FieldResult<games::GameDTO> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        games::import_game(connection, &user, document)
     }
 
     /// Start the user's participation in the given game
+    /// `shareToken` grants access to unlisted games
     field start_participation(
         &executor,
         game_id: i32,
+        share_token: Option<String>,
     ) -> FieldResult<bool> {
         let context = executor.context();
         let (connection, user) = (context.connection, &context.user);
@@ -30,7 +90,7 @@ graphql_object!( Mutation: Context as "Mutation" | &self | {
         let connection = unsafe {
             &*connection
         };
-        participations::add_participation(connection, user, game_id)
+        participations::add_participation(connection, user, game_id, share_token)
     }
 
     /// Submit a solution for checking
@@ -51,6 +111,40 @@ graphql_object!( Mutation: Context as "Mutation" | &self | {
         solutions::submit_solution(connection, user, game_id, solution)
     }
 
+    /// Stop counting the time of the user's participation in the given game
+    /// Games with a time limit cannot be paused
+    field pause_participation(&executor, game_id: i32) -> FieldResult<bool> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        participations::pause_participation(connection, user, game_id)
+    }
+
+    field resume_participation(&executor, game_id: i32) -> FieldResult<bool> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        participations::resume_participation(connection, user, game_id)
+    }
+
+    /// Give up the game and return the solutions of the puzzle
+    /// Further solutions of the user are rejected
+    field forfeit_participation(&executor, game_id: i32) -> FieldResult<Vec<SolutionDTO>> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        participations::forfeit_participation(connection, user, game_id)
+    }
+
     field publish_game(&executor, game_id: i32) -> FieldResult<bool> {
         let context = executor.context();
         let (connection, user) = (context.connection, &context.user);
@@ -61,16 +155,61 @@ graphql_object!( Mutation: Context as "Mutation" | &self | {
         games::publish_game(connection, user, game_id)
     }
 
-    field regenerate_puzzle(&executor, game_id: i32) -> FieldResult<puzzles::PuzzleDTO> {
+    /// Revert a published game to a draft
+    /// Not possible once the game has participants
+    field unpublish_game(&executor, game_id: i32) -> FieldResult<games::GameDTO> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        games::unpublish_game(connection, user, game_id)
+    }
+
+    /// Copy a game into a new draft with a freshly generated puzzle
+    field duplicate_game(&executor, game_id: i32) -> FieldResult<games::GameDTO> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        games::duplicate_game(connection, user, &context.jobs, game_id)
+    }
+
+    /// Generate a new version of the puzzle in the background
+    /// Poll the game's `generationStatus` for the result
+    /// Published games require `force` to be set
+    field regenerate_puzzle(
+        &executor,
+        game_id: i32,
+        force: Option<bool>,
+    ) -> FieldResult<games::GameDTO> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        puzzles::regenerate_puzzle(connection, user, &context.jobs, game_id, force.unwrap_or(false))
+    }
+
+    /// Make an earlier version of the puzzle the current one
+    /// Only unpublished games can be rolled back
+    field rollback_puzzle(&executor, game_id: i32, version: i32) -> FieldResult<games::GameDTO> {
         let context = executor.context();
         let (connection, user) = (context.connection, &context.user);
         let user = user.as_ref().ok_or("You need to log in first")?;
         let connection = unsafe {
             &*connection
         };
-        puzzles::regenerate_puzzle(connection, user, game_id)
+        puzzles::rollback_puzzle(connection, user, game_id, version)
     }
 
+    /// Update an unpublished game
+    /// If new words are given the puzzle is regenerated in the background,
+    /// poll `generationStatus` for the result
     field update_game(&executor, payload: games::GameUpdateDTO) -> FieldResult<games::GameDTO> {
         let context = executor.context();
         let (connection, user) = (context.connection, &context.user);
@@ -78,7 +217,197 @@ graphql_object!( Mutation: Context as "Mutation" | &self | {
         let connection = unsafe {
             &*connection
         };
-        games::update_game(connection, user, payload)
+        games::update_game(connection, user, &context.jobs, payload)
+    }
+
+    /// Create a recurring schedule that creates a new game every week
+    field add_schedule(
+        &executor,
+        schedule: schedules::ScheduleSubmissionDTO,
+    ) -> FieldResult<schedules::ScheduleDTO> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        schedules::add_schedule(connection, user, schedule)
+    }
+
+    /// Pause or resume a schedule
+    field set_schedule_active(
+        &executor,
+        schedule_id: i32,
+        active: bool,
+    ) -> FieldResult<schedules::ScheduleDTO> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        schedules::set_schedule_active(connection, user, schedule_id, active)
+    }
+
+    /// Change who can see and play the game
+    /// Unlisted games get a share token, set `resetShareToken` to replace it
+    /// Making the game public or private drops the token
+    field set_game_visibility(
+        &executor,
+        game_id: i32,
+        visibility: Visibility,
+        reset_share_token: Option<bool>,
+    ) -> FieldResult<games::GameDTO> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        games::set_game_visibility(connection, user, game_id, visibility, reset_share_token.unwrap_or(false))
+    }
+
+    /// Allow the user to see and play the private game
+    field invite_user(&executor, game_id: i32, user_id: i32) -> FieldResult<bool> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        invitations::invite_user(connection, user, game_id, user_id)
+    }
+
+    field uninvite_user(&executor, game_id: i32, user_id: i32) -> FieldResult<bool> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        invitations::uninvite_user(connection, user, game_id, user_id)
+    }
+
+    /// Share the game with the user, editors can change drafts and regenerate puzzles,
+    /// viewers can read the results
+    field add_collaborator(
+        &executor,
+        game_id: i32,
+        user_id: i32,
+        role: CollaboratorRole,
+    ) -> FieldResult<bool> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        collaborators::add_collaborator(connection, user, game_id, user_id, role)
+    }
+
+    field remove_collaborator(&executor, game_id: i32, user_id: i32) -> FieldResult<bool> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        collaborators::remove_collaborator(connection, user, game_id, user_id)
+    }
+
+    /// Delete an unpublished game and its puzzle
+    field delete_game(&executor, game_id: i32) -> FieldResult<bool> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        games::delete_game(connection, user, game_id)
+    }
+
+    /// Hide a published game from the game list
+    /// Participations and results of archived games stay readable
+    field archive_game(&executor, game_id: i32) -> FieldResult<games::GameDTO> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        games::archive_game(connection, user, game_id)
+    }
+
+    field add_word_bank(
+        &executor,
+        submission: word_banks::WordBankSubmissionDTO,
+    ) -> FieldResult<word_banks::WordBankDTO> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        word_banks::add_word_bank(connection, user, submission)
+    }
+
+    field update_word_bank(
+        &executor,
+        payload: word_banks::WordBankUpdateDTO,
+    ) -> FieldResult<word_banks::WordBankDTO> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        word_banks::update_word_bank(connection, user, payload)
+    }
+
+    /// Delete the word bank, games created from it are kept
+    field delete_word_bank(&executor, word_bank_id: i32) -> FieldResult<bool> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        word_banks::delete_word_bank(connection, user, word_bank_id)
+    }
+
+    /// Allow the user to read the word bank and create games from it
+    field share_word_bank(&executor, word_bank_id: i32, user_id: i32) -> FieldResult<bool> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        word_banks::share_word_bank(connection, user, word_bank_id, user_id)
+    }
+
+    field unshare_word_bank(&executor, word_bank_id: i32, user_id: i32) -> FieldResult<bool> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        word_banks::unshare_word_bank(connection, user, word_bank_id, user_id)
+    }
+
+    /// Create a new, unpublished game from a word bank, optionally from a random sample of its words
+    field add_game_from_word_bank(
+        &executor,
+        submission: word_banks::WordBankGameDTO,
+    ) -> FieldResult<games::GameDTO> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or("You need to log in first")?;
+        let connection = unsafe {
+            &*connection
+        };
+        word_banks::add_game_from_word_bank(connection, user, &context.jobs, submission)
     }
 });
 
@@ -1,7 +1,15 @@
-use crate::entity::game_entities::GameEntity;
-use crate::model::participation::{GameParticipation, GameParticipationEntity};
+use super::puzzles::{current_version_id, current_versions};
+use super::solutions::get_current_puzzle_solutions;
+use crate::entity::game_entities::{GameEntity, ParticipationOutcome};
+use crate::model::participation::{
+    self, close_interval, current_participation, is_paused, load_intervals, open_interval,
+    GameParticipation, GameParticipationEntity,
+};
+use crate::model::solution::SolutionDTO;
 use crate::model::user::User;
 use crate::schema;
+use crate::service::access::{check_access, load_game, Permission};
+use crate::service::time_limit::check_time_limit;
 use crate::DieselConnection;
 use chrono::{DateTime, Utc};
 use diesel::insert_into;
@@ -17,27 +25,37 @@ pub struct GameParticipationDTO {
     pub start_time: DateTime<Utc>,
     pub end_time: Option<DateTime<Utc>>,
     pub user_name: String,
+    /// `None` while the participation is in progress
+    pub outcome: Option<ParticipationOutcome>,
+    /// The time does not count while the participation is paused
+    pub paused: bool,
 }
 
-/// Get the participations for the given game
-/// Requires user to be the owner
+/// Get the finished participations for the given game, forfeits are listed by `get_forfeits`
+/// Requires user to be the owner or a collaborator
 pub fn get_all_participations(
     connection: &DieselConnection,
     current_user: &User,
     game_id: i32,
 ) -> FieldResult<Vec<GameParticipationDTO>> {
     use self::schema::game_participations::dsl::{
-        duration, game_id as gp_gid, game_participations,
+        duration, game_id as gp_gid, game_participations, outcome, puzzle_version_id,
     };
-    use self::schema::games::dsl::{games, owner_id};
+    use self::schema::games::dsl::games;
     use self::schema::users::dsl::users;
 
+    load_game(connection, current_user, game_id, Permission::ReadResults)?;
     let result = game_participations
         .filter(
             gp_gid
                 .eq(game_id)
-                .and(owner_id.eq(current_user.id))
-                .and(duration.is_not_null()),
+                .and(duration.is_not_null())
+                .and(
+                    outcome
+                        .is_null()
+                        .or(outcome.ne(ParticipationOutcome::Forfeited)),
+                )
+                .and(puzzle_version_id.nullable().eq_any(current_versions())),
         )
         .inner_join(games)
         .inner_join(users)
@@ -53,6 +71,44 @@ pub fn get_all_participations(
             start_time: parti.start_time,
             end_time: parti.end_time,
             user_name: user.name,
+            outcome: parti.outcome,
+            paused: false,
+        })
+        .collect();
+
+    Ok(result)
+}
+
+/// Get the participations of the given game the players gave up, latest first
+/// Requires user to be the owner or a collaborator
+pub fn get_forfeits(
+    connection: &DieselConnection,
+    current_user: &User,
+    game_id: i32,
+) -> FieldResult<Vec<GameParticipationDTO>> {
+    use self::schema::game_participations::dsl as gp;
+    use self::schema::users;
+
+    let game = load_game(connection, current_user, game_id, Permission::ReadResults)?;
+    let result = gp::game_participations
+        .filter(
+            gp::game_id
+                .eq(game_id)
+                .and(gp::outcome.eq(ParticipationOutcome::Forfeited))
+                .and(gp::puzzle_version_id.nullable().eq_any(current_versions())),
+        )
+        .inner_join(users::table)
+        .order_by(gp::end_time.desc())
+        .get_results::<(GameParticipationEntity, User)>(connection)?
+        .into_iter()
+        .map(|(parti, user)| GameParticipationDTO {
+            game_id: parti.game_id,
+            game_name: game.name.clone(),
+            start_time: parti.start_time,
+            end_time: parti.end_time,
+            user_name: user.name,
+            outcome: parti.outcome,
+            paused: false,
         })
         .collect();
 
@@ -64,12 +120,18 @@ pub fn get_participations(
     connection: &DieselConnection,
     current_user: &User,
 ) -> FieldResult<Vec<GameParticipationDTO>> {
-    use self::schema::game_participations::dsl::{game_participations, start_time, user_id};
+    use self::schema::game_participations::dsl::{
+        game_participations, puzzle_version_id, start_time, user_id,
+    };
     use self::schema::games::dsl::games;
     use self::schema::users::dsl::users;
 
     let result = game_participations
-        .filter(user_id.eq(current_user.id))
+        .filter(
+            user_id
+                .eq(current_user.id)
+                .and(puzzle_version_id.nullable().eq_any(current_versions())),
+        )
         .inner_join(games)
         .inner_join(users)
         .limit(100)
@@ -77,6 +139,12 @@ pub fn get_participations(
         .get_results::<(GameParticipationEntity, GameEntity, User)>(connection)
         .map_err(|_| "Failed to read games")?;
 
+    let in_progress = result
+        .iter()
+        .filter(|(parti, _, _)| parti.end_time.is_none())
+        .map(|(parti, _, _)| parti.id)
+        .collect();
+    let intervals = load_intervals(connection, in_progress)?;
     let result = result
         .into_iter()
         .map(|(parti, game, user)| GameParticipationDTO {
@@ -85,6 +153,8 @@ pub fn get_participations(
             start_time: parti.start_time,
             end_time: parti.end_time,
             user_name: user.name,
+            outcome: parti.outcome,
+            paused: is_paused(&parti, &intervals),
         })
         .collect();
 
@@ -97,46 +167,80 @@ pub fn get_participation(
     current_user: &User,
     game_id: i32,
 ) -> FieldResult<Option<GameParticipationDTO>> {
-    use self::schema::game_participations::dsl::{game_participations, user_id};
+    use self::schema::game_participations::dsl::{game_participations, puzzle_version_id, user_id};
     use self::schema::games::dsl::{games, id as gid};
 
     let result = game_participations
-        .filter(user_id.eq(current_user.id).and(gid.eq(game_id)))
+        .filter(
+            user_id
+                .eq(current_user.id)
+                .and(gid.eq(game_id))
+                .and(puzzle_version_id.nullable().eq_any(current_versions())),
+        )
         .inner_join(games)
         .get_result::<(GameParticipationEntity, GameEntity)>(connection)
-        .optional()?
-        .map(|(parti, game)| GameParticipationDTO {
-            game_id: parti.game_id,
-            game_name: game.name,
-            start_time: parti.start_time,
-            end_time: parti.end_time,
-            user_name: current_user.name.clone(),
-        });
+        .optional()?;
+    let intervals = match result {
+        Some((ref parti, _)) => load_intervals(connection, vec![parti.id])?,
+        None => vec![],
+    };
+    let result = result.map(|(parti, game)| GameParticipationDTO {
+        game_id: parti.game_id,
+        game_name: game.name,
+        start_time: parti.start_time,
+        end_time: parti.end_time,
+        user_name: current_user.name.clone(),
+        outcome: parti.outcome,
+        paused: is_paused(&parti, &intervals),
+    });
 
     Ok(result)
 }
 
+/// `share_token` grants access to unlisted games
 pub fn add_participation(
     connection: &DieselConnection,
     current_user: &User,
     game_id: i32,
+    share_token: Option<String>,
 ) -> FieldResult<bool> {
     use crate::schema::game_participations::dsl;
+    use crate::schema::games;
 
     if is_participating(connection, current_user, game_id)? {
         Err("User is already participating in the game")?;
     }
+    let game = games::table
+        .filter(games::dsl::id.eq(game_id))
+        .get_result::<GameEntity>(connection)
+        .optional()?
+        .ok_or("Game not found")?;
+    check_access(
+        connection,
+        &game,
+        Some(current_user),
+        share_token.as_deref(),
+    )?;
+    game.status(Utc::now()).check_live()?;
 
+    let version = current_version_id(connection, game_id)?.ok_or("The puzzle is not ready yet")?;
+    let now = Utc::now();
     let participation = GameParticipation {
         game_id: game_id,
         user_id: current_user.id,
-        start_time: Utc::now(),
+        start_time: now,
         end_time: None,
+        puzzle_version_id: version,
     };
 
-    insert_into(dsl::game_participations)
-        .values(&participation)
-        .execute(connection)?;
+    connection.transaction::<_, diesel::result::Error, _>(|| {
+        let participation_id = insert_into(dsl::game_participations)
+            .values(&participation)
+            .returning(dsl::id)
+            .get_result::<i32>(connection)?;
+        open_interval(connection, participation_id, now)?;
+        Ok(())
+    })?;
 
     Ok(true)
 }
@@ -146,30 +250,126 @@ pub fn end_participation(
     current_user: &User,
     game_id: i32,
 ) -> FieldResult<bool> {
-    use crate::schema::game_participations::dsl;
-    use diesel::update;
+    let participation = current_participation(connection, game_id, current_user.id)?
+        .ok_or("User is not participating in the game")?;
+
+    participation::end_participation(
+        connection,
+        &participation,
+        ParticipationOutcome::Completed,
+        Utc::now(),
+    )?;
+
+    Ok(true)
+}
+
+/// Give up the game, the solutions of the puzzle are revealed to the player
+/// Requires a participation in progress, further solutions of the player are rejected
+pub fn forfeit_participation(
+    connection: &DieselConnection,
+    current_user: &User,
+    game_id: i32,
+) -> FieldResult<Vec<SolutionDTO>> {
+    let game = find_game(connection, game_id)?;
+    let now = Utc::now();
+    let participation = current_participation(connection, game_id, current_user.id)?;
+    check_time_limit(connection, &game, participation.as_ref(), now)?;
+    let participation = playing(connection, current_user, game_id)?;
+
+    participation::end_participation(
+        connection,
+        &participation,
+        ParticipationOutcome::Forfeited,
+        now,
+    )?;
+
+    let result = get_current_puzzle_solutions(connection, game_id)
+        .ok_or("Unexpected error retrieving the game")?;
+    Ok(result)
+}
 
-    if !is_participating(connection, current_user, game_id)? {
-        Err("User is not participating in the game")?;
+/// Stop counting the time of the participation until it is resumed
+/// Not possible in competitive games, which have a time limit
+pub fn pause_participation(
+    connection: &DieselConnection,
+    current_user: &User,
+    game_id: i32,
+) -> FieldResult<bool> {
+    let game = find_game(connection, game_id)?;
+    if game.time_limit.is_some() {
+        Err("Games with a time limit cannot be paused")?;
+    }
+    let participation = playing(connection, current_user, game_id)?;
+    let now = Utc::now();
+    let intervals = load_intervals(connection, vec![participation.id])?;
+    if is_paused(&participation, &intervals) {
+        Err("The participation is already paused")?;
     }
 
-    update(dsl::game_participations)
-        .filter(
-            dsl::user_id
-                .eq(current_user.id)
-                .and(dsl::game_id.eq(game_id)),
-        )
-        .set(dsl::end_time.eq(Utc::now()))
-        .execute(connection)?;
+    connection.transaction::<_, diesel::result::Error, _>(|| {
+        if intervals.is_empty() {
+            // Started before the play intervals were recorded
+            open_interval(connection, participation.id, participation.start_time)?;
+        }
+        close_interval(connection, participation.id, now)?;
+        Ok(())
+    })?;
+    Ok(true)
+}
 
+pub fn resume_participation(
+    connection: &DieselConnection,
+    current_user: &User,
+    game_id: i32,
+) -> FieldResult<bool> {
+    let game = find_game(connection, game_id)?;
+    let now = Utc::now();
+    game.status(now).check_live()?;
+    let participation = playing(connection, current_user, game_id)?;
+    let intervals = load_intervals(connection, vec![participation.id])?;
+    if !is_paused(&participation, &intervals) {
+        Err("The participation is not paused")?;
+    }
+
+    open_interval(connection, participation.id, now)?;
     Ok(true)
 }
 
+fn find_game(connection: &DieselConnection, game_id: i32) -> FieldResult<GameEntity> {
+    use crate::schema::games::dsl::games;
+
+    let game = games
+        .find(game_id)
+        .get_result::<GameEntity>(connection)
+        .optional()?
+        .ok_or("Game not found")?;
+    Ok(game)
+}
+
+/// The participation of the user in progress on the current puzzle of the game
+fn playing(
+    connection: &DieselConnection,
+    current_user: &User,
+    game_id: i32,
+) -> FieldResult<GameParticipationEntity> {
+    let participation = current_participation(connection, game_id, current_user.id)?
+        .ok_or("User is not participating in the game")?;
+    if participation.end_time.is_some() {
+        Err("The participation has already ended")?;
+    }
+    Ok(participation)
+}
+
 fn is_participating(connection: &DieselConnection, user: &User, game_id: i32) -> FieldResult<bool> {
     use super::super::schema::game_participations::dsl;
 
     let result = dsl::game_participations
-        .filter(dsl::game_id.eq(game_id).and(dsl::user_id.eq(user.id)))
+        .filter(
+            dsl::game_id
+                .eq(game_id)
+                .and(dsl::user_id.eq(user.id))
+                .and(dsl::puzzle_version_id.nullable().eq_any(current_versions())),
+        )
         .count()
         .get_result::<i64>(connection)
         .optional()?
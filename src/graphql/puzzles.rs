@@ -1,13 +1,19 @@
+use super::games::GameDTO;
 use super::*;
-use crate::entity::puzzle_entities::{PuzzleEntity, PuzzleUpdate};
+use crate::entity::game_entities::{GameEntity, GenerationStatus};
+use crate::entity::puzzle_entities::{PuzzleEntity, PuzzleUpdate, PuzzleVersionEntity};
+use crate::model::game_status::{status_filter, GameStatus};
+use crate::model::interchange::{self, GameMetadata, PuzzleDocument};
+use crate::model::participation::{current_participation, is_paused, load_intervals};
 use crate::model::puzzle::Puzzle;
 use crate::model::user::User;
 use crate::schema;
-use chrono::Utc;
-use diesel::dsl::{delete, update};
+use crate::service::access::{check_access, load_game, shared_games, Permission};
+use crate::service::generation::GenerationJobs;
+use chrono::{DateTime, Utc};
+use diesel::dsl::{update, Select};
 use diesel::prelude::*;
-use diesel::result::Error as DieselError;
-use juniper::{self, FieldResult};
+use juniper::{self, FieldError, FieldResult};
 
 #[derive(GraphQLObject, Debug)]
 pub struct PuzzleDTO {
@@ -18,6 +24,31 @@ pub struct PuzzleDTO {
     pub words: Vec<String>,
 }
 
+/// A puzzle the game had at some point
+/// Participations and solutions are bound to the version they were played on
+#[derive(GraphQLObject, Debug)]
+pub struct PuzzleVersionDTO {
+    pub game_id: i32,
+    pub version: i32,
+    pub is_current: bool,
+    pub created_at: DateTime<Utc>,
+    pub created_by: String,
+    pub game_table: Vec<String>,
+    pub columns: i32,
+    pub rows: i32,
+    pub words: Vec<String>,
+    /// Seeds are 64 bit, GraphQL integers are not
+    pub seed: Option<String>,
+}
+
+#[derive(GraphQLEnum, Debug, Clone, Copy)]
+pub enum PuzzleExportFormat {
+    /// Perplexio's own versioned format, can be imported again
+    Perplexio,
+    /// ipuz word search, for use with other tools
+    Ipuz,
+}
+
 pub fn fetch_puzzle_by_game_id(
     connection: &DieselConnection,
     current_user: &User,
@@ -27,22 +58,40 @@ pub fn fetch_puzzle_by_game_id(
     use self::schema::games as g;
     use self::schema::puzzles::dsl;
 
+    let game = g::table
+        .find(game_id)
+        .get_result::<GameEntity>(connection)
+        .optional()?
+        .ok_or("Game not found")?;
+    check_access(connection, &game, Some(current_user), None)?;
+    // The clock of a paused participation is stopped, so the grid stays hidden until resumed
+    if let Some(participation) = current_participation(connection, game_id, current_user.id)? {
+        let intervals = load_intervals(connection, vec![participation.id])?;
+        if is_paused(&participation, &intervals) {
+            Err("The participation is paused, resume it to see the puzzle")?;
+        }
+    }
+
+    let is_public = status_filter(&GameStatus::PUBLIC, Utc::now());
     let result = dsl::puzzles
         .inner_join(g::table)
-        .left_outer_join(gp::table.on(gp::dsl::game_id.eq(dsl::game_id)))
-        .filter(dsl::game_id.eq(game_id))
-        .filter(
-            g::available_from
-                .gt(Utc::now())
-                .and(g::available_from.lt(Utc::now())),
+        .left_outer_join(
+            gp::table.on(gp::dsl::game_id
+                .eq(dsl::game_id)
+                .and(gp::dsl::puzzle_version_id.nullable().eq(dsl::version_id))),
         )
+        .filter(dsl::game_id.eq(game_id))
+        .filter(g::generation_status.eq(GenerationStatus::Succeeded))
         .filter(
             // check if the user has permissions
             gp::user_id
                 .eq(current_user.id)
-                .or(g::dsl::owner_id.eq(current_user.id)),
+                .and(is_public)
+                .or(g::dsl::owner_id.eq(current_user.id))
+                .or(g::dsl::id.eq_any(shared_games(current_user.id))),
         )
         .select(dsl::puzzles::all_columns())
+        .into_boxed()
         .get_result::<PuzzleEntity>(connection)
         .map(|entity| Puzzle::from(entity))
         .map(|puzzle| {
@@ -59,57 +108,208 @@ pub fn fetch_puzzle_by_game_id(
     Ok(result)
 }
 
+/// Generate a new version of the puzzle in the background
+/// Poll the game's `generation_status` for the result
+/// Results stay bound to the version they were played on
+/// Published games are only regenerated if `force` is set, as people may be playing them
+/// Requires user to be the owner or an editor
 pub fn regenerate_puzzle(
     connection: &DieselConnection,
     current_user: &User,
+    jobs: &GenerationJobs,
     game_id: i32,
-) -> FieldResult<PuzzleDTO> {
-    use self::schema::game_participations as gp;
+    force: bool,
+) -> FieldResult<GameDTO> {
     use self::schema::games as g;
     use self::schema::puzzles as p;
-    use self::schema::solutions as s;
 
-    let result = connection.transaction::<_, DieselError, _>(|| {
+    let (game, words) = connection.transaction::<_, FieldError, _>(|| {
+        let game = load_game(connection, current_user, game_id, Permission::Edit)?;
         let puzzle = p::table
             .filter(p::dsl::game_id.eq(game_id))
-            .inner_join(g::table)
-            .filter(g::dsl::owner_id.eq(current_user.id))
-            .select(p::table::all_columns())
-            .get_result::<PuzzleEntity>(connection)?;
+            .get_result::<PuzzleEntity>(connection)
+            .optional()?
+            .ok_or("Game not found")?;
 
-        delete(s::table)
-            .filter(s::game_id.eq(game_id))
-            .execute(connection)?;
+        if game.generation_status == GenerationStatus::Pending {
+            Err("The puzzle is already being generated")?;
+        }
+        if game.archived {
+            Err("Archived games can not be regenerated")?;
+        }
+        if game.published && !force {
+            Err("The game is published, regenerating it starts a new version without the current results. Set `force` to proceed")?;
+        }
 
-        delete(gp::table)
-            .filter(gp::game_id.eq(game_id))
-            .execute(connection)?;
+        let game = update(g::table.filter(g::dsl::id.eq(game_id)))
+            .set((
+                g::dsl::generation_status.eq(GenerationStatus::Pending),
+                g::dsl::generation_error.eq(None::<String>),
+            ))
+            .get_result::<GameEntity>(connection)?;
 
-        let puzzle = Puzzle::from_words(puzzle.words, 200).map_err(|e| {
-            error!("Failed to generate puzzle {:?}", e);
-            DieselError::RollbackTransaction
-        })?;
+        Ok((game, puzzle.words))
+    })?;
 
-        let puzzle = PuzzleUpdate::from(puzzle);
+    jobs.generate_puzzle(game_id, current_user.id, words);
 
-        update(p::table)
-            .filter(p::dsl::game_id.eq(game_id))
-            .set(puzzle)
-            .get_result::<PuzzleEntity>(connection)
+    Ok(GameDTO::load_owner(connection, game, current_user)?)
+}
+
+/// Get every version of the game's puzzle, newest first
+/// Requires user to be the owner or a collaborator
+pub fn fetch_puzzle_versions(
+    connection: &DieselConnection,
+    current_user: &User,
+    game_id: i32,
+) -> FieldResult<Vec<PuzzleVersionDTO>> {
+    use self::schema::puzzle_versions as pv;
+    use self::schema::users as u;
+
+    load_game(connection, current_user, game_id, Permission::ReadResults)?;
+    let current = current_version_id(connection, game_id)?;
+    let result = pv::table
+        .inner_join(u::table)
+        .filter(pv::dsl::game_id.eq(game_id))
+        .order_by(pv::dsl::version.desc())
+        .select((pv::table::all_columns(), u::dsl::name))
+        .get_results::<(PuzzleVersionEntity, String)>(connection)?
+        .into_iter()
+        .map(|(version, created_by)| {
+            let is_current = current == Some(version.id);
+            PuzzleVersionDTO {
+                game_id: version.game_id,
+                version: version.version,
+                is_current: is_current,
+                created_at: version.created_at,
+                created_by: created_by,
+                game_table: version
+                    .game_table
+                    .chars()
+                    .collect::<Vec<_>>()
+                    .chunks(version.table_columns.max(1) as usize)
+                    .map(|row| row.iter().collect())
+                    .collect(),
+                columns: version.table_columns,
+                rows: version.table_rows,
+                words: version.words,
+                seed: version.seed.map(|s| (s as u64).to_string()),
+            }
+        })
+        .collect();
+
+    Ok(result)
+}
+
+/// Make an earlier version of the puzzle the current one
+/// Results played on that version become the current results again
+/// Requires user to be the owner or an editor and the game to be unpublished
+pub fn rollback_puzzle(
+    connection: &DieselConnection,
+    current_user: &User,
+    game_id: i32,
+    version: i32,
+) -> FieldResult<GameDTO> {
+    use self::schema::games as g;
+    use self::schema::puzzle_versions as pv;
+    use self::schema::puzzles as p;
+
+    let game = connection.transaction::<_, FieldError, _>(|| {
+        let game = load_game(connection, current_user, game_id, Permission::Edit)?;
+        if game.published {
+            Err("Published games can not be rolled back")?;
+        }
+        if game.generation_status == GenerationStatus::Pending {
+            Err("The puzzle is being generated")?;
+        }
+
+        let version = pv::table
+            .filter(
+                pv::dsl::game_id
+                    .eq(game_id)
+                    .and(pv::dsl::version.eq(version)),
+            )
+            .get_result::<PuzzleVersionEntity>(connection)
+            .optional()?
+            .ok_or("Version not found")?;
+
+        update(p::table.filter(p::dsl::game_id.eq(game_id)))
+            .set(PuzzleUpdate::from(version))
+            .execute(connection)?;
+
+        let game = update(g::table.filter(g::dsl::id.eq(game_id)))
+            .set((
+                g::dsl::generation_status.eq(GenerationStatus::Succeeded),
+                g::dsl::generation_error.eq(None::<String>),
+            ))
+            .get_result::<GameEntity>(connection)?;
+
+        Ok(game)
     })?;
 
-    let result = Puzzle::from(result);
+    Ok(GameDTO::load_owner(connection, game, current_user)?)
+}
 
-    let (columns, rows) = result.get_shape();
+/// The current version of every game
+/// Filter results of the current puzzles by `puzzle_version_id.nullable().eq_any(current_versions())`
+pub fn current_versions() -> Select<schema::puzzles::table, schema::puzzles::version_id> {
+    use self::schema::puzzles::dsl;
 
-    let result = PuzzleDTO {
-        game_id: game_id,
-        game_table: result.render_table(),
-        columns: columns as i32,
-        rows: rows as i32,
-        words: result.get_words().clone(),
-    };
+    dsl::puzzles.select(dsl::version_id)
+}
 
-    Ok(result)
+/// Get the id of the game's current puzzle version
+/// `None` if no puzzle has been generated yet
+pub fn current_version_id(connection: &DieselConnection, game_id: i32) -> QueryResult<Option<i32>> {
+    use self::schema::puzzles::dsl;
+
+    let result = dsl::puzzles
+        .filter(dsl::game_id.eq(game_id))
+        .select(dsl::version_id)
+        .get_result::<Option<i32>>(connection)
+        .optional()?;
+    Ok(result.and_then(|v| v))
 }
 
+/// Export the puzzle of the game as a JSON document
+/// Requires user to be the owner or an editor, as the export contains the solutions
+pub fn export_puzzle(
+    connection: &DieselConnection,
+    current_user: &User,
+    game_id: i32,
+    format: PuzzleExportFormat,
+) -> FieldResult<String> {
+    use self::schema::games as g;
+    use self::schema::puzzles as p;
+    use self::schema::users as u;
+
+    load_game(connection, current_user, game_id, Permission::Edit)?;
+    let (puzzle, (game, owner)) = p::table
+        .inner_join(g::table.inner_join(u::table))
+        .filter(
+            p::dsl::game_id
+                .eq(game_id)
+                .and(g::dsl::generation_status.eq(GenerationStatus::Succeeded)),
+        )
+        .get_result::<(PuzzleEntity, (GameEntity, User))>(connection)
+        .optional()?
+        .ok_or("Game not found")?;
+
+    let puzzle = Puzzle::from(puzzle);
+    let game = GameMetadata {
+        name: game.name,
+        owner: Some(owner.name),
+        available_from: game.available_from,
+        available_to: game.available_to,
+        description: game.description,
+        tags: game.tags,
+        language: game.language,
+        difficulty: game.difficulty.map(|d| d.as_str().to_string()),
+    };
+
+    let result = match format {
+        PuzzleExportFormat::Perplexio => PuzzleDocument::new(&puzzle, Some(game)).to_json(),
+        PuzzleExportFormat::Ipuz => interchange::to_ipuz(&puzzle, Some(&game)),
+    };
+    Ok(result.to_string())
+}
@@ -19,22 +19,89 @@ graphql_object!(Query: Context as "Query" |&self| {
 
     field games(
         &executor,
-        page: Option<i32>
+        page: Option<i32>,
+        filter: Option<games::GameFilter>,
+        sort: Option<games::GameSort>,
     ) -> FieldResult<games::PaginatedGames> {
         let context = executor.context();
         let (connection,user) = (context.connection, &context.user);
         let connection = unsafe {&* connection};
-        games::fetch_games(connection, user, page)
+        games::fetch_games(connection, user, page, filter, sort)
     }
 
+    /// Every game of the current user with participation statistics
+    field my_games(
+        &executor,
+        page: Option<i32>,
+        filter: Option<games::GameFilter>,
+        sort: Option<games::GameSort>,
+    ) -> FieldResult<games::PaginatedMyGames> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or_else(||"You have to log in first")?;
+        let connection = unsafe {&* connection};
+        games::fetch_my_games(connection, user, page, filter, sort)
+    }
+
+    /// `shareToken` grants access to unlisted games
     field game(
         &executor,
-        id: i32
+        id: i32,
+        share_token: Option<String>,
     ) -> FieldResult<games::GameDTO> {
         let context = executor.context();
         let (connection,user) = (context.connection, &context.user);
         let connection = unsafe {&* connection};
-        games::fetch_game_by_id(connection, user, id)
+        games::fetch_game_by_id(connection, user, id, share_token)
+    }
+
+    /// Get the users invited to the game
+    /// Requires user to be the owner
+    field game_invitations(
+        &executor,
+        game_id: i32,
+    ) -> FieldResult<Vec<UserInfo>> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or_else(||"You have to log in first")?;
+        let connection = unsafe {&* connection};
+        invitations::fetch_invitations(connection, user, game_id)
+    }
+
+    /// Get the people the game is shared with
+    /// Requires user to be the owner
+    field game_collaborators(
+        &executor,
+        game_id: i32,
+    ) -> FieldResult<Vec<collaborators::CollaboratorDTO>> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or_else(||"You have to log in first")?;
+        let connection = unsafe {&* connection};
+        collaborators::fetch_collaborators(connection, user, game_id)
+    }
+
+    /// Themed word lists `randomGame` picks words from
+    field dictionaries() -> Vec<dictionaries::DictionaryDTO> {
+        dictionaries::fetch_dictionaries()
+    }
+
+    /// Suggest the words of a puzzle from a pasted text, e.g. a paragraph of a lesson
+    /// Stop words of the language are dropped, `language` defaults to `en`, `wordCount` to 10
+    field suggest_words(
+        text: String,
+        language: Option<String>,
+        word_count: Option<i32>,
+    ) -> FieldResult<Vec<String>> {
+        dictionaries::suggest_words(text, language, word_count)
+    }
+
+    /// Today's daily puzzle, the same game for everyone
+    field daily_puzzle(&executor) -> FieldResult<Option<daily::DailyPuzzleDTO>> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let connection = unsafe {&* connection};
+        daily::fetch_daily_puzzle(connection, user)
     }
 
     field puzzle(
@@ -48,8 +115,89 @@ graphql_object!(Query: Context as "Query" |&self| {
         puzzles::fetch_puzzle_by_game_id(connection, &user, game_id)
     }
 
-    /// Get the participations for the given game
+    /// Get every version of the game's puzzle, newest first
+    /// Requires user to be the owner or a collaborator
+    field puzzle_versions(
+        &executor,
+        game_id: i32,
+    ) -> FieldResult<Vec<puzzles::PuzzleVersionDTO>> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or_else(||"You have to log in first")?;
+        let connection = unsafe {&* connection};
+        puzzles::fetch_puzzle_versions(connection, &user, game_id)
+    }
+
+    /// Export the puzzle of the game as a JSON document
+    /// Requires user to be the owner or an editor
+    field export_puzzle(
+        &executor,
+        game_id: i32,
+        format: Option<puzzles::PuzzleExportFormat>,
+    ) -> FieldResult<String> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or_else(||"You have to log in first")?;
+        let connection = unsafe {&* connection};
+        let format = format.unwrap_or(puzzles::PuzzleExportFormat::Perplexio);
+        puzzles::export_puzzle(connection, &user, game_id, format)
+    }
+
+    /// Export games with their puzzles as a JSON bundle that `importGames` accepts on any instance
+    /// Results are only included if `includeResults` is set, `anonymise` replaces the player names
+    /// Requires user to be the owner or an editor of every game
+    field export_games(
+        &executor,
+        game_ids: Vec<i32>,
+        include_results: Option<bool>,
+        anonymise: Option<bool>,
+    ) -> FieldResult<String> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or_else(||"You have to log in first")?;
+        let connection = unsafe {&* connection};
+        bundles::export_games(
+            connection,
+            user,
+            game_ids,
+            include_results.unwrap_or(false),
+            anonymise.unwrap_or(false),
+        )
+    }
+
+    /// Get the recurring schedules of the current user
+    field schedules(&executor) -> FieldResult<Vec<schedules::ScheduleDTO>> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or_else(||"You have to log in first")?;
+        let connection = unsafe {&* connection};
+        schedules::fetch_schedules(connection, user)
+    }
+
+    /// Get the word banks of the current user and the ones shared with them
+    field word_banks(&executor) -> FieldResult<Vec<word_banks::WordBankDTO>> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or_else(||"You have to log in first")?;
+        let connection = unsafe {&* connection};
+        word_banks::fetch_word_banks(connection, user)
+    }
+
+    /// Get the users the word bank is shared with
     /// Requires user to be the owner
+    field word_bank_shares(
+        &executor,
+        word_bank_id: i32,
+    ) -> FieldResult<Vec<UserInfo>> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or_else(||"You have to log in first")?;
+        let connection = unsafe {&* connection};
+        word_banks::fetch_word_bank_shares(connection, user, word_bank_id)
+    }
+
+    /// Get the participations for the given game
+    /// Requires user to be the owner or a collaborator
     field all_participations_by_game(
         &executor,
         game_id: i32,
@@ -61,6 +209,46 @@ graphql_object!(Query: Context as "Query" |&self| {
         participations::get_all_participations(connection, user, game_id)
     }
 
+    /// Rank the players who completed the game, the fastest first
+    /// `shareToken` grants access to unlisted games
+    field game_leaderboard(
+        &executor,
+        game_id: i32,
+        share_token: Option<String>,
+        page: Option<i32>,
+        per_page: Option<i32>,
+    ) -> FieldResult<leaderboards::LeaderboardDTO> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let connection = unsafe {&* connection};
+        leaderboards::fetch_game_leaderboard(connection, user, game_id, share_token, page, per_page)
+    }
+
+    /// Rank the players by the public games they completed, then by their total time
+    field global_leaderboard(
+        &executor,
+        page: Option<i32>,
+        per_page: Option<i32>,
+    ) -> FieldResult<leaderboards::LeaderboardDTO> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let connection = unsafe {&* connection};
+        leaderboards::fetch_global_leaderboard(connection, user, page, per_page)
+    }
+
+    /// Get the participations the players of the given game gave up
+    /// Requires user to be the owner or a collaborator
+    field forfeits_by_game(
+        &executor,
+        game_id: i32,
+    ) -> FieldResult<Vec<participations::GameParticipationDTO>> {
+        let context = executor.context();
+        let (connection, user) = (context.connection, &context.user);
+        let user = user.as_ref().ok_or_else(||"You have to log in first")?;
+        let connection = unsafe {&* connection};
+        participations::get_forfeits(connection, user, game_id)
+    }
+
     /// Get the participations of the current user
     field participations(&executor) -> FieldResult<Vec<participations::GameParticipationDTO>> {
         let context = executor.context();
@@ -90,7 +278,8 @@ graphql_object!(Query: Context as "Query" |&self| {
         solutions::get_solution_by_game_id(connection, user, game_id)
     }
 
-    /// Return all solutions of the game. Only if the current user is the owner of the game
+    /// Return all solutions of the game
+    /// Only if the current user is the owner, a collaborator or forfeited the game
     field get_all_solutions(&executor, game_id: i32) -> FieldResult<Vec<SolutionDTO>> {
         let context = executor.context();
         let (connection, user) = (context.connection, &context.user);
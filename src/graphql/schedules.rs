@@ -0,0 +1,161 @@
+use super::games::validate_words;
+use crate::entity::schedule_entities::{GameScheduleEntity, GameScheduleInsert, Weekday};
+use crate::model::schedule::Recurrence;
+use crate::model::user::User;
+use crate::DieselConnection;
+use chrono::{DateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
+use diesel::dsl::{insert_into, update};
+use diesel::prelude::*;
+use juniper::FieldResult;
+
+const TIME_FORMAT: &str = "%H:%M";
+
+/// Creates a new game every week, see `service::scheduler`
+#[derive(GraphQLObject, Debug)]
+pub struct ScheduleDTO {
+    pub id: i32,
+    pub name: String,
+    pub words: Vec<String>,
+    pub words_per_game: i32,
+    pub time_zone: String,
+    pub start_day: Weekday,
+    /// Local time in `HH:MM` format
+    pub start_time: String,
+    pub end_day: Weekday,
+    /// Local time in `HH:MM` format
+    pub end_time: String,
+    /// Start of the next game to be created
+    pub next_start: DateTime<Utc>,
+    pub active: bool,
+}
+
+impl From<GameScheduleEntity> for ScheduleDTO {
+    fn from(schedule: GameScheduleEntity) -> Self {
+        Self {
+            id: schedule.id,
+            name: schedule.name,
+            words: schedule.words,
+            words_per_game: schedule.words_per_game,
+            time_zone: schedule.time_zone,
+            start_day: schedule.start_day,
+            start_time: schedule.start_time.format(TIME_FORMAT).to_string(),
+            end_day: schedule.end_day,
+            end_time: schedule.end_time.format(TIME_FORMAT).to_string(),
+            next_start: schedule.next_start,
+            active: schedule.active,
+        }
+    }
+}
+
+#[derive(GraphQLInputObject, Debug)]
+pub struct ScheduleSubmissionDTO {
+    /// Games are named after the schedule and the day they start on
+    pub name: String,
+    /// Pool of words the games are generated from
+    pub words: Vec<String>,
+    /// Number of words picked from the pool for each game, defaults to every word
+    pub words_per_game: Option<i32>,
+    /// Name of a time zone in the tz database, e.g. `Europe/Budapest`
+    pub time_zone: String,
+    pub start_day: Weekday,
+    /// Local time in `HH:MM` format
+    pub start_time: String,
+    pub end_day: Weekday,
+    /// Local time in `HH:MM` format
+    pub end_time: String,
+}
+
+pub fn fetch_schedules(
+    connection: &DieselConnection,
+    current_user: &User,
+) -> FieldResult<Vec<ScheduleDTO>> {
+    use crate::schema::game_schedules::dsl;
+
+    let result = dsl::game_schedules
+        .filter(dsl::owner_id.eq(current_user.id))
+        .order_by(dsl::id)
+        .load::<GameScheduleEntity>(connection)?
+        .into_iter()
+        .map(ScheduleDTO::from)
+        .collect();
+    Ok(result)
+}
+
+pub fn add_schedule(
+    connection: &DieselConnection,
+    current_user: &User,
+    submission: ScheduleSubmissionDTO,
+) -> FieldResult<ScheduleDTO> {
+    use crate::schema::game_schedules::dsl;
+
+    validate_words(&submission.words)?;
+    let words_per_game = submission
+        .words_per_game
+        .unwrap_or(submission.words.len() as i32);
+    if words_per_game < 1 || words_per_game as usize > submission.words.len() {
+        Err("wordsPerGame must be between 1 and the number of words")?;
+    }
+    let recurrence = Recurrence {
+        time_zone: submission
+            .time_zone
+            .parse::<Tz>()
+            .map_err(|_| "Unknown time zone")?,
+        start_day: submission.start_day.into(),
+        start_time: parse_time(&submission.start_time)?,
+        end_day: submission.end_day.into(),
+        end_time: parse_time(&submission.end_time)?,
+    };
+
+    let schedule = GameScheduleInsert {
+        owner_id: current_user.id,
+        name: submission.name,
+        words: submission.words,
+        words_per_game: words_per_game,
+        time_zone: submission.time_zone,
+        start_day: submission.start_day,
+        start_time: recurrence.start_time,
+        end_day: submission.end_day,
+        end_time: recurrence.end_time,
+        next_start: recurrence.next_period(Utc::now()).start,
+    };
+    let result = insert_into(dsl::game_schedules)
+        .values(schedule)
+        .get_result::<GameScheduleEntity>(connection)?;
+    Ok(result.into())
+}
+
+/// Pause or resume a schedule
+/// Resumed schedules continue with the next period that has not started yet
+pub fn set_schedule_active(
+    connection: &DieselConnection,
+    current_user: &User,
+    schedule_id: i32,
+    active: bool,
+) -> FieldResult<ScheduleDTO> {
+    use crate::schema::game_schedules::dsl;
+
+    let query = dsl::game_schedules.filter(
+        dsl::id
+            .eq(schedule_id)
+            .and(dsl::owner_id.eq(current_user.id)),
+    );
+    let schedule = query
+        .get_result::<GameScheduleEntity>(connection)
+        .optional()?
+        .ok_or("Schedule not found")?;
+
+    let next_start = if active && !schedule.active {
+        schedule.recurrence()?.next_period(Utc::now()).start
+    } else {
+        schedule.next_start
+    };
+    let result = update(query)
+        .set((dsl::active.eq(active), dsl::next_start.eq(next_start)))
+        .get_result::<GameScheduleEntity>(connection)?;
+    Ok(result.into())
+}
+
+fn parse_time(time: &str) -> Result<NaiveTime, &'static str> {
+    NaiveTime::parse_from_str(time, TIME_FORMAT).map_err(|_| "Times must be in HH:MM format")
+}
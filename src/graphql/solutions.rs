@@ -1,8 +1,14 @@
 use super::participations::end_participation;
-use crate::entity::game_entities::GameEntity;
-use crate::model::solution::{SolutionDTO, SolutionEntity, SolutionForm};
+use super::puzzles::{current_version_id, current_versions};
+use crate::entity::game_entities::{GameEntity, GenerationStatus};
+use crate::model::participation::{
+    check_accepts_solutions, current_participation, load_intervals, reveals_solutions,
+};
+use crate::model::solution::{completes_puzzle, SolutionDTO, SolutionEntity, SolutionForm};
 use crate::model::user::User;
 use crate::model::vector::Vector;
+use crate::service::access::{check_access, load_game, Permission};
+use crate::service::time_limit::check_time_limit;
 use crate::DieselConnection;
 use chrono::Utc;
 use diesel::insert_into;
@@ -28,10 +34,15 @@ pub fn get_users_solutions(
     current_user: &User,
     game_id: i32,
 ) -> Result<Vec<SolutionDTO>, DieselError> {
-    use crate::schema::solutions::dsl::{game_id as gid, solutions, user_id};
+    use crate::schema::solutions::dsl::{game_id as gid, puzzle_version_id, solutions, user_id};
 
     let result = solutions
-        .filter(user_id.eq(current_user.id).and(gid.eq(game_id)))
+        .filter(
+            user_id
+                .eq(current_user.id)
+                .and(gid.eq(game_id))
+                .and(puzzle_version_id.nullable().eq_any(current_versions())),
+        )
         .get_results::<SolutionEntity>(connection)?
         .into_iter()
         .map(|solution| {
@@ -45,21 +56,16 @@ pub fn get_users_solutions(
     Ok(result)
 }
 
-/// Return all solutions of the game. Only if the current user is the owner of the game
+/// Return all solutions of the game
+/// Only if the current user is the owner, a collaborator or forfeited the game
 pub fn get_all_solutions(
     connection: &DieselConnection,
     current_user: &User,
     game_id: i32,
 ) -> FieldResult<Vec<SolutionDTO>> {
-    use crate::schema::games::dsl;
-
-    if dsl::games
-        .filter(dsl::id.eq(game_id).and(dsl::owner_id.eq(current_user.id)))
-        .count()
-        .get_result::<i64>(connection)?
-        == 0
-    {
-        Err("Game not found")?;
+    let participation = current_participation(connection, game_id, current_user.id)?;
+    if !reveals_solutions(participation.as_ref()) {
+        load_game(connection, current_user, game_id, Permission::ReadResults)?;
     }
     let result = get_current_puzzle_solutions(connection, game_id)
         .ok_or("Unexpected error retrieving the game")?;
@@ -81,16 +87,22 @@ pub fn submit_solution(
             .filter(g::id.eq(game_id))
             .get_result(connection)?;
 
-        if game.available_to.map(|a| a < now).unwrap_or(false) {
-            Err("Game has expired")?;
+        check_access(connection, &game, Some(current_user), None)?;
+        game.status(now).check_live()?;
+        if game.generation_status != GenerationStatus::Succeeded {
+            Err("The puzzle is not ready yet")?;
         }
-        if game.available_from.map(|a| now < a).unwrap_or(true) {
-            Err("Game not available")?;
+        let participation = current_participation(connection, game_id, current_user.id)?;
+        check_time_limit(connection, &game, participation.as_ref(), now)?;
+        if let Some(participation) = participation {
+            let intervals = load_intervals(connection, vec![participation.id])?;
+            check_accepts_solutions(&participation, &intervals)?;
         }
     }
 
     let puzzle_solutions =
         get_current_puzzle_solutions(&connection, game_id).ok_or("Game does not exist")?;
+    let version = current_version_id(connection, game_id)?.ok_or("Game does not exist")?;
     let result = &puzzle_solutions.iter().find(|s| **s == solution);
     if result.is_none() {
         return Ok(false);
@@ -113,10 +125,11 @@ pub fn submit_solution(
                     y1: solution.solution1.y,
                     x2: solution.solution2.x,
                     y2: solution.solution2.y,
+                    puzzle_version_id: version,
                 })
                 .execute(connection)?;
         }
-        if current_solutions.len() + 1 == puzzle_solutions.len() {
+        if completes_puzzle(&current_solutions, result, &puzzle_solutions) {
             end_participation(connection, current_user, game_id).map_err(|e| {
                 error!("Failed to end participation {:?}", e);
                 DieselError::RollbackTransaction
@@ -127,7 +140,7 @@ pub fn submit_solution(
     Ok(true)
 }
 
-fn get_current_puzzle_solutions(
+pub fn get_current_puzzle_solutions(
     connection: &DieselConnection,
     gid: i32,
 ) -> Option<Vec<SolutionDTO>> {
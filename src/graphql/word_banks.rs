@@ -0,0 +1,319 @@
+use super::games::{self, validate_words, GameDTO, GameSubmissionDTO};
+use crate::entity::word_bank_entities::{
+    WordBankEntity, WordBankInsert, WordBankShareEntity, WordBankUpdate,
+};
+use crate::model::user::{User, UserInfo};
+use crate::schema::word_bank_shares;
+use crate::service::generation::GenerationJobs;
+use crate::DieselConnection;
+use chrono::{DateTime, Utc};
+use diesel::dsl::{delete, insert_into, update, Eq, Filter, Select};
+use diesel::prelude::*;
+use juniper::FieldResult;
+use rand::seq::SliceRandom;
+
+/// A named list of words games can be created from
+#[derive(GraphQLObject, Debug)]
+pub struct WordBankDTO {
+    pub id: i32,
+    pub name: String,
+    pub owner: String,
+    pub is_owner: bool,
+    pub words: Vec<String>,
+}
+
+impl WordBankDTO {
+    fn from_entity(bank: WordBankEntity, owner: String, is_owner: bool) -> Self {
+        Self {
+            id: bank.id,
+            name: bank.name,
+            owner: owner,
+            is_owner: is_owner,
+            words: bank.words,
+        }
+    }
+}
+
+#[derive(GraphQLInputObject, Debug)]
+pub struct WordBankSubmissionDTO {
+    pub name: String,
+    pub words: Vec<String>,
+}
+
+#[derive(GraphQLInputObject, Debug)]
+pub struct WordBankUpdateDTO {
+    pub word_bank_id: i32,
+    pub name: Option<String>,
+    /// Replace the words of the word bank
+    pub words: Option<Vec<String>>,
+}
+
+#[derive(GraphQLInputObject, Debug)]
+pub struct WordBankGameDTO {
+    pub word_bank_id: i32,
+    pub name: String,
+    /// Number of words picked randomly from the word bank, defaults to every word
+    pub sample_size: Option<i32>,
+    pub available_from: Option<DateTime<Utc>>,
+    pub available_to: Option<DateTime<Utc>>,
+}
+
+/// Word banks of the current user and the ones shared with them
+pub fn fetch_word_banks(
+    connection: &DieselConnection,
+    current_user: &User,
+) -> FieldResult<Vec<WordBankDTO>> {
+    use crate::schema::users;
+    use crate::schema::word_banks::dsl as w;
+
+    let result = w::word_banks
+        .inner_join(users::table)
+        .filter(
+            w::owner_id
+                .eq(current_user.id)
+                .or(w::id.eq_any(shared_word_banks(current_user.id))),
+        )
+        .order_by(w::name)
+        .load::<(WordBankEntity, User)>(connection)?
+        .into_iter()
+        .map(|(bank, owner)| {
+            let is_owner = owner.id == current_user.id;
+            WordBankDTO::from_entity(bank, owner.name, is_owner)
+        })
+        .collect();
+    Ok(result)
+}
+
+pub fn add_word_bank(
+    connection: &DieselConnection,
+    current_user: &User,
+    submission: WordBankSubmissionDTO,
+) -> FieldResult<WordBankDTO> {
+    use crate::schema::word_banks::dsl;
+
+    validate_bank_words(&submission.words)?;
+    let bank = insert_into(dsl::word_banks)
+        .values(WordBankInsert {
+            owner_id: current_user.id,
+            name: submission.name,
+            words: submission.words,
+        })
+        .get_result::<WordBankEntity>(connection)?;
+    Ok(WordBankDTO::from_entity(
+        bank,
+        current_user.name.clone(),
+        true,
+    ))
+}
+
+/// Requires user to be the owner
+pub fn update_word_bank(
+    connection: &DieselConnection,
+    current_user: &User,
+    changeset: WordBankUpdateDTO,
+) -> FieldResult<WordBankDTO> {
+    use crate::schema::word_banks::dsl;
+
+    if let Some(words) = changeset.words.as_ref() {
+        validate_bank_words(words)?;
+    }
+    let query = dsl::word_banks.filter(
+        dsl::id
+            .eq(changeset.word_bank_id)
+            .and(dsl::owner_id.eq(current_user.id)),
+    );
+    let bank = if changeset.name.is_some() || changeset.words.is_some() {
+        update(query)
+            .set(WordBankUpdate {
+                name: changeset.name,
+                words: changeset.words,
+            })
+            .get_result::<WordBankEntity>(connection)
+    } else {
+        query.get_result::<WordBankEntity>(connection)
+    };
+    let bank = bank.optional()?.ok_or("Word bank not found")?;
+    Ok(WordBankDTO::from_entity(
+        bank,
+        current_user.name.clone(),
+        true,
+    ))
+}
+
+/// Delete the word bank, games created from it are kept
+/// Requires user to be the owner
+pub fn delete_word_bank(
+    connection: &DieselConnection,
+    current_user: &User,
+    word_bank_id: i32,
+) -> FieldResult<bool> {
+    use crate::schema::word_banks::dsl;
+
+    let deleted = delete(
+        dsl::word_banks.filter(
+            dsl::id
+                .eq(word_bank_id)
+                .and(dsl::owner_id.eq(current_user.id)),
+        ),
+    )
+    .execute(connection)?;
+    if deleted == 0 {
+        Err("Word bank not found")?;
+    }
+    Ok(true)
+}
+
+/// Get the users the word bank is shared with
+/// Requires user to be the owner
+pub fn fetch_word_bank_shares(
+    connection: &DieselConnection,
+    current_user: &User,
+    word_bank_id: i32,
+) -> FieldResult<Vec<UserInfo>> {
+    use crate::schema::users;
+    use crate::schema::word_bank_shares::dsl as s;
+
+    load_word_bank(connection, current_user, word_bank_id, true)?;
+    let result = s::word_bank_shares
+        .filter(s::word_bank_id.eq(word_bank_id))
+        .inner_join(users::table)
+        .select(users::all_columns)
+        .order_by(users::dsl::name)
+        .load::<User>(connection)?
+        .into_iter()
+        .map(UserInfo::from)
+        .collect();
+    Ok(result)
+}
+
+/// Allow the user to read the word bank and create games from it
+pub fn share_word_bank(
+    connection: &DieselConnection,
+    current_user: &User,
+    word_bank_id: i32,
+    user_id: i32,
+) -> FieldResult<bool> {
+    use crate::schema::users::dsl as u;
+    use crate::schema::word_bank_shares::dsl as s;
+
+    load_word_bank(connection, current_user, word_bank_id, true)?;
+    u::users
+        .find(user_id)
+        .select(u::id)
+        .get_result::<i32>(connection)
+        .optional()?
+        .ok_or("User not found")?;
+
+    insert_into(s::word_bank_shares)
+        .values(WordBankShareEntity {
+            word_bank_id: word_bank_id,
+            user_id: user_id,
+        })
+        .on_conflict_do_nothing()
+        .execute(connection)?;
+    Ok(true)
+}
+
+/// Games the user already created from the word bank are kept
+pub fn unshare_word_bank(
+    connection: &DieselConnection,
+    current_user: &User,
+    word_bank_id: i32,
+    user_id: i32,
+) -> FieldResult<bool> {
+    use crate::schema::word_bank_shares::dsl as s;
+
+    load_word_bank(connection, current_user, word_bank_id, true)?;
+    let deleted = delete(
+        s::word_bank_shares.filter(s::word_bank_id.eq(word_bank_id).and(s::user_id.eq(user_id))),
+    )
+    .execute(connection)?;
+    Ok(deleted > 0)
+}
+
+/// Create a new, unpublished game from the words of a word bank, see `add_game`
+pub fn add_game_from_word_bank(
+    connection: &DieselConnection,
+    current_user: &User,
+    jobs: &GenerationJobs,
+    submission: WordBankGameDTO,
+) -> FieldResult<GameDTO> {
+    let bank = load_word_bank(connection, current_user, submission.word_bank_id, false)?;
+    let words = match submission.sample_size {
+        Some(size) if size < 1 || size as usize > bank.words.len() => {
+            Err("sampleSize must be between 1 and the number of words")?
+        }
+        Some(size) => bank
+            .words
+            .choose_multiple(&mut rand::thread_rng(), size as usize)
+            .cloned()
+            .collect(),
+        None => bank.words,
+    };
+
+    games::add_game(
+        connection,
+        current_user,
+        jobs,
+        GameSubmissionDTO {
+            name: submission.name,
+            words: words,
+            available_from: submission.available_from,
+            available_to: submission.available_to,
+            description: None,
+            tags: None,
+            language: None,
+            difficulty: None,
+            time_limit: None,
+        },
+    )
+}
+
+/// Load a word bank of the user, or one shared with them unless `owned` is set
+fn load_word_bank(
+    connection: &DieselConnection,
+    current_user: &User,
+    word_bank_id: i32,
+    owned: bool,
+) -> FieldResult<WordBankEntity> {
+    use crate::schema::word_banks::dsl;
+
+    let query = dsl::word_banks
+        .filter(dsl::id.eq(word_bank_id))
+        .into_boxed();
+    let query = if owned {
+        query.filter(dsl::owner_id.eq(current_user.id))
+    } else {
+        query.filter(
+            dsl::owner_id
+                .eq(current_user.id)
+                .or(dsl::id.eq_any(shared_word_banks(current_user.id))),
+        )
+    };
+    let bank = query
+        .get_result::<WordBankEntity>(connection)
+        .optional()?
+        .ok_or("Word bank not found")?;
+    Ok(bank)
+}
+
+/// Ids of the word banks shared with the user
+fn shared_word_banks(
+    user_id: i32,
+) -> Filter<
+    Select<word_bank_shares::table, word_bank_shares::word_bank_id>,
+    Eq<word_bank_shares::user_id, i32>,
+> {
+    use crate::schema::word_bank_shares::dsl;
+
+    dsl::word_bank_shares
+        .select(dsl::word_bank_id)
+        .filter(dsl::user_id.eq(user_id))
+}
+
+fn validate_bank_words(words: &[String]) -> Result<(), &'static str> {
+    if words.is_empty() {
+        return Err("Word banks must have at least one word");
+    }
+    validate_words(words)
+}
@@ -2,6 +2,7 @@ pub mod users;
 
 use crate::graphql::{Context, Schema};
 use crate::service::auth;
+use crate::service::generation::GenerationJobs;
 use crate::ConnectionPool;
 use crate::DieselConnection;
 use actix_identity::Identity;
@@ -25,6 +26,7 @@ pub fn graphql_handler(
     data: web::Json<GraphQLRequest>,
     schema: web::Data<Arc<Schema>>,
     pool: web::Data<ConnectionPool>,
+    jobs: web::Data<GenerationJobs>,
 ) -> impl Future<Item = HttpResponse, Error = Error> {
     let connection: &DieselConnection = &pool.get().unwrap();
     let user = id
@@ -35,6 +37,7 @@ pub fn graphql_handler(
         let context = Context {
             connection: connection as *const DieselConnection,
             user: user,
+            jobs: jobs.get_ref().clone(),
         };
         let res = data.execute(&schema, &context);
         serde_json::to_string(&res)
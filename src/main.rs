@@ -9,6 +9,7 @@ extern crate diesel;
 #[macro_use]
 extern crate juniper;
 
+#[macro_use]
 pub mod entity;
 pub mod graphql;
 pub mod handler;
@@ -18,6 +19,8 @@ pub mod service;
 
 use crate::graphql::{mutation::Mutation, query::Query, Schema};
 use crate::service::config::Config;
+use crate::service::generation::{self, GenerationJobs};
+use crate::service::scheduler;
 use actix_cors::Cors;
 use actix_identity::{CookieIdentityPolicy, IdentityService};
 use actix_web::http::header;
@@ -41,6 +44,15 @@ fn main() {
         .build(manager)
         .expect("Failed to create pool.");
 
+    generation::fail_interrupted(&pool.get().expect("Failed to get a connection"))
+        .expect("Failed to clean up interrupted puzzle generations");
+    let jobs = GenerationJobs::new(
+        pool.clone(),
+        config.generation_timeout,
+        config.generation_workers,
+    );
+    scheduler::start(pool.clone(), jobs.clone());
+
     let bind = format!("{}:{}", config.address, config.port);
 
     HttpServer::new(move || {
@@ -54,6 +66,7 @@ fn main() {
             .data(config.clone())
             .data(schema.clone())
             .data(pool.clone())
+            .data(jobs.clone())
             .wrap(
                 cors.allowed_methods(vec!["GET", "POST"])
                     .allowed_headers(vec![header::CONTENT_TYPE, header::AUTHORIZATION])
@@ -0,0 +1,210 @@
+//! Archive of several games for moving them between instances, e.g. from staging to production
+//!
+//! A bundle is a JSON object of the following shape:
+//!
+//! ```json
+//! {
+//!     "format": "perplexio-bundle",
+//!     "version": 1,
+//!     "exportedAt": "2019-08-18T10:00:00Z",
+//!     "games": [{
+//!         "document": {
+//!             "format": "perplexio-puzzle",
+//!             "version": 1,
+//!             "game": {
+//!                 "name": "Fruits",
+//!                 "description": "Fruits of the season",
+//!                 "tags": ["fruits"],
+//!                 "language": "en",
+//!                 "difficulty": "easy",
+//!                 ...
+//!             },
+//!             "puzzle": { ... }
+//!         },
+//!         "timeLimit": 300,
+//!         "results": [{
+//!             "player": "Player 1",
+//!             "startTime": "2019-08-01T10:00:00Z",
+//!             "endTime": "2019-08-01T10:03:20Z",
+//!             "duration": 200,
+//!             "foundWords": 10
+//!         }]
+//!     }]
+//! }
+//! ```
+//!
+//! - `document` holds the game, its metadata and its puzzle in the puzzle interchange format,
+//!   see `interchange`
+//! - `results` is omitted unless the results were exported, players are named `Player N` if anonymised
+//!
+//! Bundles with a higher `version` than `BUNDLE_FORMAT_VERSION` are rejected.
+use super::interchange::{ImportError, PuzzleDocument};
+use chrono::{DateTime, Utc};
+use serde_json::Value as JsonValue;
+
+pub const BUNDLE_FORMAT_NAME: &str = "perplexio-bundle";
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GameBundle {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub games: Vec<BundledGame>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BundledGame {
+    pub document: PuzzleDocument,
+    /// Maximum play time of a participation in seconds
+    #[serde(default)]
+    pub time_limit: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub results: Option<Vec<BundledResult>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BundledResult {
+    pub player: String,
+    pub start_time: DateTime<Utc>,
+    #[serde(default)]
+    pub end_time: Option<DateTime<Utc>>,
+    /// In seconds, for finished participations
+    #[serde(default)]
+    pub duration: Option<i32>,
+    pub found_words: i32,
+}
+
+impl GameBundle {
+    pub fn new(games: Vec<BundledGame>, exported_at: DateTime<Utc>) -> Self {
+        Self {
+            format: BUNDLE_FORMAT_NAME.to_string(),
+            version: BUNDLE_FORMAT_VERSION,
+            exported_at: exported_at,
+            games: games,
+        }
+    }
+
+    /// Parse the bundle, the games are validated with `PuzzleDocument::to_puzzle` on import
+    pub fn from_json(bundle: &str) -> Result<Self, ImportError> {
+        let bundle: Self =
+            serde_json::from_str(bundle).map_err(|e| ImportError::Malformed(e.to_string()))?;
+        if bundle.format != BUNDLE_FORMAT_NAME {
+            return Err(ImportError::UnsupportedFormat(bundle.format));
+        }
+        if bundle.version == 0 || bundle.version > BUNDLE_FORMAT_VERSION {
+            return Err(ImportError::UnsupportedVersion(bundle.version));
+        }
+        for game in bundle.games.iter() {
+            game.document.check_format()?;
+        }
+        Ok(bundle)
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        serde_json::to_value(self).expect("Failed to serialize game bundle")
+    }
+}
+
+/// Replace the names of the players with `Player N`, numbered in the order of the results
+pub fn anonymise(results: &mut [BundledResult]) {
+    for (i, result) in results.iter_mut().enumerate() {
+        result.player = format!("Player {}", i + 1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::interchange::GameMetadata;
+    use crate::model::puzzle::Puzzle;
+    use chrono::TimeZone;
+
+    fn bundled_game(results: Option<Vec<BundledResult>>) -> BundledGame {
+        let words = vec!["apple".to_string(), "pear".to_string()];
+        let puzzle = Puzzle::from_words_seeded(words, 1000, 42).unwrap();
+        let game = GameMetadata {
+            name: "Fruits".to_string(),
+            owner: Some("Daniel".to_string()),
+            available_from: None,
+            available_to: None,
+            description: None,
+            tags: vec!["fruits".to_string()],
+            language: Some("en".to_string()),
+            difficulty: Some("easy".to_string()),
+        };
+        BundledGame {
+            document: PuzzleDocument::new(&puzzle, Some(game)),
+            time_limit: Some(300),
+            results: results,
+        }
+    }
+
+    fn result(player: &str) -> BundledResult {
+        BundledResult {
+            player: player.to_string(),
+            start_time: Utc.ymd(2019, 8, 1).and_hms(10, 0, 0),
+            end_time: None,
+            duration: None,
+            found_words: 1,
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let bundle = GameBundle::new(
+            vec![
+                bundled_game(Some(vec![result("Alice")])),
+                bundled_game(None),
+            ],
+            Utc.ymd(2019, 8, 18).and_hms(10, 0, 0),
+        );
+
+        let json = bundle.to_json().to_string();
+
+        assert_eq!(GameBundle::from_json(&json).unwrap(), bundle);
+        assert!(!json.contains("\"results\":null"));
+    }
+
+    #[test]
+    fn test_rejects_unknown_format_and_version() {
+        let mut bundle = GameBundle::new(vec![], Utc::now()).to_json();
+        bundle["version"] = json!(BUNDLE_FORMAT_VERSION + 1);
+        assert_eq!(
+            GameBundle::from_json(&bundle.to_string()),
+            Err(ImportError::UnsupportedVersion(BUNDLE_FORMAT_VERSION + 1))
+        );
+
+        bundle["format"] = json!("perplexio-puzzle");
+        assert_eq!(
+            GameBundle::from_json(&bundle.to_string()),
+            Err(ImportError::UnsupportedFormat(
+                "perplexio-puzzle".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_rejects_unknown_document_version() {
+        let mut bundle = GameBundle::new(vec![bundled_game(None)], Utc::now()).to_json();
+        bundle["games"][0]["document"]["version"] = json!(99);
+
+        assert_eq!(
+            GameBundle::from_json(&bundle.to_string()),
+            Err(ImportError::UnsupportedVersion(99))
+        );
+    }
+
+    #[test]
+    fn test_anonymise() {
+        let mut results = vec![result("Alice"), result("Bob")];
+
+        anonymise(&mut results);
+
+        assert_eq!(results[0].player, "Player 1");
+        assert_eq!(results[1].player, "Player 2");
+    }
+}
@@ -0,0 +1,228 @@
+//! Themed word lists bundled with the server, see the `dictionaries` directory
+//! Every file holds one lowercase ASCII word per line, accented words are left out
+use crate::entity::game_entities::Difficulty;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::ops::RangeInclusive;
+
+pub struct Dictionary {
+    pub theme: &'static str,
+    /// ISO 639-1 code of the language
+    pub language: &'static str,
+    words: &'static str,
+}
+
+pub const DICTIONARIES: &[Dictionary] = &[
+    Dictionary {
+        theme: "animals",
+        language: "en",
+        words: include_str!("../../dictionaries/en/animals.txt"),
+    },
+    Dictionary {
+        theme: "countries",
+        language: "en",
+        words: include_str!("../../dictionaries/en/countries.txt"),
+    },
+    Dictionary {
+        theme: "fruits",
+        language: "en",
+        words: include_str!("../../dictionaries/en/fruits.txt"),
+    },
+    Dictionary {
+        theme: "animals",
+        language: "de",
+        words: include_str!("../../dictionaries/de/animals.txt"),
+    },
+    Dictionary {
+        theme: "countries",
+        language: "de",
+        words: include_str!("../../dictionaries/de/countries.txt"),
+    },
+    Dictionary {
+        theme: "fruits",
+        language: "de",
+        words: include_str!("../../dictionaries/de/fruits.txt"),
+    },
+    Dictionary {
+        theme: "animals",
+        language: "es",
+        words: include_str!("../../dictionaries/es/animals.txt"),
+    },
+    Dictionary {
+        theme: "countries",
+        language: "es",
+        words: include_str!("../../dictionaries/es/countries.txt"),
+    },
+    Dictionary {
+        theme: "fruits",
+        language: "es",
+        words: include_str!("../../dictionaries/es/fruits.txt"),
+    },
+    Dictionary {
+        theme: "animals",
+        language: "fr",
+        words: include_str!("../../dictionaries/fr/animals.txt"),
+    },
+    Dictionary {
+        theme: "countries",
+        language: "fr",
+        words: include_str!("../../dictionaries/fr/countries.txt"),
+    },
+    Dictionary {
+        theme: "fruits",
+        language: "fr",
+        words: include_str!("../../dictionaries/fr/fruits.txt"),
+    },
+];
+
+impl Dictionary {
+    pub fn find(theme: &str, language: &str) -> Option<&'static Dictionary> {
+        DICTIONARIES
+            .iter()
+            .find(|d| d.theme == theme && d.language == language)
+    }
+
+    pub fn words(&self) -> Vec<&'static str> {
+        self.words
+            .lines()
+            .map(|word| word.trim())
+            .filter(|word| !word.is_empty())
+            .collect()
+    }
+
+    /// Pick `count` different words, or every word if the dictionary is smaller
+    pub fn pick_words<R: Rng>(&self, count: usize, rng: &mut R) -> Vec<String> {
+        self.words()
+            .choose_multiple(rng, count)
+            .map(|word| word.to_string())
+            .collect()
+    }
+
+    /// Pick `count` different words, preferring the lengths of the difficulty
+    /// Words of other lengths are only picked if there are not enough, closest lengths first
+    pub fn pick_words_by_difficulty<R: Rng>(
+        &self,
+        difficulty: Difficulty,
+        count: usize,
+        rng: &mut R,
+    ) -> Vec<String> {
+        let lengths = word_lengths(difficulty);
+        let mut words = self.words();
+        words.shuffle(rng);
+        words.sort_by_key(|word| distance(&lengths, word.len()));
+        words
+            .into_iter()
+            .take(count)
+            .map(|word| word.to_string())
+            .collect()
+    }
+}
+
+/// Longer words are harder to find
+fn word_lengths(difficulty: Difficulty) -> RangeInclusive<usize> {
+    match difficulty {
+        Difficulty::Easy => 3..=5,
+        Difficulty::Medium => 5..=7,
+        Difficulty::Hard => 8..=usize::MAX,
+    }
+}
+
+fn distance(range: &RangeInclusive<usize>, length: usize) -> usize {
+    if length < *range.start() {
+        range.start() - length
+    } else if length > *range.end() {
+        length - range.end()
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_dictionaries_hold_valid_words() {
+        for dictionary in DICTIONARIES {
+            let words = dictionary.words();
+            let unique = words.iter().collect::<HashSet<_>>();
+
+            assert!(words.len() >= 20, "{} is too small", dictionary.theme);
+            assert_eq!(
+                unique.len(),
+                words.len(),
+                "{} has duplicates",
+                dictionary.theme
+            );
+            for word in words {
+                assert!(word.len() >= 3, "{} is too short", word);
+                assert!(
+                    word.chars().all(|c| c.is_ascii_lowercase()),
+                    "{} is not lowercase ASCII",
+                    word
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_same_seed_picks_same_words() {
+        let dictionary = Dictionary::find("animals", "en").unwrap();
+
+        let a = dictionary.pick_words(10, &mut StdRng::seed_from_u64(42));
+        let b = dictionary.pick_words(10, &mut StdRng::seed_from_u64(42));
+
+        assert_eq!(a.len(), 10);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_every_language_has_the_same_themes() {
+        for dictionary in DICTIONARIES {
+            for other in DICTIONARIES {
+                assert!(Dictionary::find(dictionary.theme, other.language).is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn test_easy_picks_short_words() {
+        let dictionary = Dictionary::find("fruits", "en").unwrap();
+
+        let words =
+            dictionary.pick_words_by_difficulty(Difficulty::Easy, 5, &mut StdRng::seed_from_u64(1));
+
+        assert_eq!(words.len(), 5);
+        assert!(words.iter().all(|w| w.len() <= 5), "{:?}", words);
+    }
+
+    #[test]
+    fn test_hard_picks_long_words() {
+        let dictionary = Dictionary::find("countries", "de").unwrap();
+
+        let words =
+            dictionary.pick_words_by_difficulty(Difficulty::Hard, 8, &mut StdRng::seed_from_u64(1));
+
+        assert_eq!(words.len(), 8);
+        assert!(words.iter().all(|w| w.len() >= 8), "{:?}", words);
+    }
+
+    #[test]
+    fn test_closest_lengths_fill_up_missing_words() {
+        let dictionary = Dictionary::find("fruits", "es").unwrap();
+        let short = dictionary.words().iter().filter(|w| w.len() <= 5).count();
+
+        let words = dictionary.pick_words_by_difficulty(
+            Difficulty::Easy,
+            short + 3,
+            &mut StdRng::seed_from_u64(1),
+        );
+
+        assert_eq!(words.len(), short + 3);
+        assert_eq!(words.iter().filter(|w| w.len() <= 5).count(), short);
+        assert!(words.iter().all(|w| w.len() <= 6), "{:?}", words);
+    }
+}
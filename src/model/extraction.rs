@@ -0,0 +1,133 @@
+//! Suggest the words of a puzzle from free text, e.g. a paragraph of a lesson
+//! Puzzles are generated from bytes, so words with letters outside of ASCII are left out
+use std::collections::HashMap;
+
+const MIN_LENGTH: usize = 3;
+
+const STOP_WORDS: &[(&str, &[&str])] = &[
+    (
+        "en",
+        &[
+            "about", "after", "all", "also", "and", "any", "are", "because", "been", "before",
+            "being", "but", "can", "could", "did", "does", "for", "from", "had", "has", "have",
+            "her", "hers", "him", "his", "how", "into", "its", "just", "more", "most", "not",
+            "now", "only", "other", "our", "out", "over", "she", "should", "some", "such", "than",
+            "that", "the", "their", "them", "then", "there", "these", "they", "this", "those",
+            "through", "very", "was", "were", "what", "when", "where", "which", "while", "who",
+            "why", "will", "with", "would", "you", "your",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "aber", "alle", "als", "auch", "auf", "aus", "bei", "bin", "bis", "das", "dass", "dem",
+            "den", "der", "des", "die", "dies", "diese", "dieser", "doch", "ein", "eine", "einem",
+            "einen", "einer", "eines", "oder", "ich", "ihr", "ihre", "ist", "mit", "nach", "nicht",
+            "noch", "nur", "sein", "seine", "sich", "sie", "sind", "und", "uns", "unter", "vom",
+            "von", "war", "waren", "was", "weil", "wenn", "wie", "wir", "wird", "zum", "zur",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "aunque", "como", "con", "cual", "cuando", "del", "desde", "donde", "dos", "ella",
+            "ellas", "ellos", "entre", "era", "eran", "esa", "ese", "eso", "esta", "este", "esto",
+            "hay", "las", "los", "mas", "muy", "nos", "para", "pero", "por", "porque", "que",
+            "sin", "sobre", "son", "sus", "tambien", "tiene", "todo", "todos", "una", "uno",
+            "unos", "unas",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "aux", "avec", "avait", "ces", "cette", "dans", "des", "donc", "elle", "elles", "est",
+            "leur", "leurs", "les", "lui", "mais", "mes", "nous", "par", "pas", "plus", "pour",
+            "que", "qui", "sans", "ses", "son", "sont", "sur", "tous", "tout", "une", "vous",
+        ],
+    ),
+];
+
+/// Rank the words of the text by frequency, then by length, and return the first `count`
+/// Words shorter than 3 letters and stop words of the language are dropped
+pub fn extract_words(
+    text: &str,
+    language: &str,
+    count: usize,
+) -> Result<Vec<String>, &'static str> {
+    let stop_words = stop_words(language).ok_or("Unsupported language")?;
+
+    let mut frequencies = HashMap::new();
+    for word in tokenise(text) {
+        if word.len() >= MIN_LENGTH && !stop_words.contains(&word.as_str()) {
+            *frequencies.entry(word).or_insert(0) += 1;
+        }
+    }
+
+    let mut words = frequencies.into_iter().collect::<Vec<_>>();
+    words.sort_by(|(a, a_count), (b, b_count)| {
+        b_count
+            .cmp(a_count)
+            .then(b.len().cmp(&a.len()))
+            .then(a.cmp(b))
+    });
+    Ok(words
+        .into_iter()
+        .take(count)
+        .map(|(word, _)| word)
+        .collect())
+}
+
+/// Lowercase words of the text, words with letters outside of ASCII are dropped
+fn tokenise(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty() && word.is_ascii())
+        .map(|word| word.to_ascii_lowercase())
+}
+
+fn stop_words(language: &str) -> Option<&'static [&'static str]> {
+    STOP_WORDS
+        .iter()
+        .find(|(l, _)| *l == language)
+        .map(|(_, words)| *words)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tokenise() {
+        let words = tokenise("The cell's nucleus, (DNA) and café-au-lait!").collect::<Vec<_>>();
+
+        assert_eq!(
+            words,
+            vec!["the", "cell", "s", "nucleus", "dna", "and", "au", "lait"]
+        );
+    }
+
+    #[test]
+    fn test_ranks_by_frequency_then_length() {
+        let text = "Plants need light. Plants turn light into sugar, photosynthesis makes sugar and oxygen. Plants grow.";
+
+        let words = extract_words(text, "en", 5).unwrap();
+
+        assert_eq!(
+            words,
+            vec!["plants", "light", "sugar", "photosynthesis", "oxygen"]
+        );
+    }
+
+    #[test]
+    fn test_drops_stop_words_of_the_language() {
+        let text = "Der Hund und die Katze sind im Garten";
+
+        let words = extract_words(text, "de", 10).unwrap();
+
+        assert_eq!(words, vec!["garten", "katze", "hund"]);
+    }
+
+    #[test]
+    fn test_unsupported_language() {
+        assert!(extract_words("some text", "xx", 10).is_err());
+    }
+}
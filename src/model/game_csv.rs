@@ -0,0 +1,297 @@
+//! Games described in CSV, one game per row, for creating many games at once
+//! The first row names the columns: `name` and `words` are required, `available_from`,
+//! `available_to` and `tags` are optional
+//! Words and tags are separated by `;`, dates are in RFC 3339 format, e.g. `2019-09-02T08:00:00Z`
+use chrono::{DateTime, Utc};
+
+const LIST_SEPARATOR: char = ';';
+
+#[derive(Debug, PartialEq)]
+pub struct GameRow {
+    pub name: String,
+    pub words: Vec<String>,
+    pub available_from: Option<DateTime<Utc>>,
+    pub available_to: Option<DateTime<Utc>>,
+    pub tags: Vec<String>,
+}
+
+/// A parsed row with the line it starts on, counted from 1
+pub type ParsedRow = (usize, Result<GameRow, String>);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Column {
+    Name,
+    Words,
+    AvailableFrom,
+    AvailableTo,
+    Tags,
+}
+
+const COLUMNS: [Column; 5] = [
+    Column::Name,
+    Column::Words,
+    Column::AvailableFrom,
+    Column::AvailableTo,
+    Column::Tags,
+];
+
+impl Column {
+    fn name(self) -> &'static str {
+        match self {
+            Column::Name => "name",
+            Column::Words => "words",
+            Column::AvailableFrom => "available_from",
+            Column::AvailableTo => "available_to",
+            Column::Tags => "tags",
+        }
+    }
+
+    fn parse(header: &str) -> Result<Column, String> {
+        let header = header.trim().to_lowercase();
+        COLUMNS
+            .iter()
+            .cloned()
+            .find(|column| column.name() == header)
+            .ok_or_else(|| format!("Unknown column {}", header))
+    }
+}
+
+/// Parse the rows of the CSV, every row is parsed on its own so errors can be reported per row
+/// Fails as a whole if the CSV is malformed or the header is invalid
+pub fn parse_games(text: &str) -> Result<Vec<ParsedRow>, String> {
+    let mut records = parse_records(text)?.into_iter();
+    let columns = records
+        .next()
+        .ok_or("The CSV is empty")?
+        .1
+        .iter()
+        .map(|header| Column::parse(header))
+        .collect::<Result<Vec<_>, _>>()?;
+    for required in &[Column::Name, Column::Words] {
+        if !columns.contains(required) {
+            return Err(format!("Missing column {}", required.name()));
+        }
+    }
+
+    let rows = records
+        .filter(|(_, record)| record.iter().any(|field| !field.trim().is_empty()))
+        .map(|(line, record)| (line, parse_row(&columns, record)))
+        .collect();
+    Ok(rows)
+}
+
+fn parse_row(columns: &[Column], record: Vec<String>) -> Result<GameRow, String> {
+    if record.len() != columns.len() {
+        return Err(format!(
+            "Expected {} fields, found {}",
+            columns.len(),
+            record.len()
+        ));
+    }
+    let mut row = GameRow {
+        name: String::new(),
+        words: vec![],
+        available_from: None,
+        available_to: None,
+        tags: vec![],
+    };
+    for (column, field) in columns.iter().zip(record) {
+        let field = field.trim();
+        match column {
+            Column::Name => row.name = field.to_string(),
+            Column::Words => row.words = split_list(field),
+            Column::AvailableFrom => row.available_from = parse_date(field)?,
+            Column::AvailableTo => row.available_to = parse_date(field)?,
+            Column::Tags => row.tags = split_list(field),
+        }
+    }
+    if row.name.is_empty() {
+        return Err("The name is empty".to_string());
+    }
+    if row.words.is_empty() {
+        return Err("There are no words".to_string());
+    }
+    Ok(row)
+}
+
+fn split_list(field: &str) -> Vec<String> {
+    field
+        .split(LIST_SEPARATOR)
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(|item| item.to_string())
+        .collect()
+}
+
+fn parse_date(field: &str) -> Result<Option<DateTime<Utc>>, String> {
+    if field.is_empty() {
+        return Ok(None);
+    }
+    DateTime::parse_from_rfc3339(field)
+        .map(|date| Some(date.with_timezone(&Utc)))
+        .map_err(|_| format!("Invalid date {}, use RFC 3339 format", field))
+}
+
+/// Split the text into records of fields, with the line they start on
+/// Fields may be quoted to hold commas, line breaks and quotes, the latter escaped as `""`
+fn parse_records(text: &str) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            '"' => return Err(format!("Unexpected quote on line {}", line)),
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => (),
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push((start, std::mem::take(&mut record)));
+                line += 1;
+                start = line;
+            }
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(format!("Unterminated quote on line {}", line));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((start, record));
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_records() {
+        let text = "a,b,c\r\n1,\"two, three\",\"say \"\"hi\"\"\"\n\"multi\nline\",,x";
+
+        let (lines, records): (Vec<_>, Vec<_>) = parse_records(text).unwrap().into_iter().unzip();
+
+        assert_eq!(lines, vec![1, 2, 3]);
+        assert_eq!(
+            records,
+            vec![
+                vec!["a", "b", "c"],
+                vec!["1", "two, three", "say \"hi\""],
+                vec!["multi\nline", "", "x"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_records_errors() {
+        assert_eq!(
+            parse_records("a,b\n\"open,c\n").unwrap_err(),
+            "Unterminated quote on line 3"
+        );
+        assert_eq!(
+            parse_records("a,b\nx\"y,c").unwrap_err(),
+            "Unexpected quote on line 2"
+        );
+    }
+
+    #[test]
+    fn test_parse_games() {
+        let text = "name,words,available_from,tags\n\
+                    Week 1,apple; pear ;plum,2019-09-02T08:00:00+02:00,fruits;kids\n\
+                    \n\
+                    Week 2,cat;dog,,\n";
+
+        let rows = parse_games(text).unwrap();
+
+        assert_eq!(
+            rows,
+            vec![
+                (
+                    2,
+                    Ok(GameRow {
+                        name: "Week 1".to_string(),
+                        words: vec!["apple".to_string(), "pear".to_string(), "plum".to_string()],
+                        available_from: Some(Utc.ymd(2019, 9, 2).and_hms(6, 0, 0)),
+                        available_to: None,
+                        tags: vec!["fruits".to_string(), "kids".to_string()],
+                    }),
+                ),
+                (
+                    4,
+                    Ok(GameRow {
+                        name: "Week 2".to_string(),
+                        words: vec!["cat".to_string(), "dog".to_string()],
+                        available_from: None,
+                        available_to: None,
+                        tags: vec![],
+                    }),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_games_reports_errors_per_row() {
+        let text = "words,name\ncat;dog,Pets\ncat,\n,Empty\ncat,Bad,extra\n";
+
+        let rows = parse_games(text).unwrap();
+
+        assert!(rows[0].1.is_ok());
+        assert_eq!(rows[1].1, Err("The name is empty".to_string()));
+        assert_eq!(rows[2].1, Err("There are no words".to_string()));
+        assert_eq!(rows[3].1, Err("Expected 2 fields, found 3".to_string()));
+    }
+
+    #[test]
+    fn test_parse_games_reports_source_lines() {
+        let text = "name,words\n\nPets,cat;dog\n\"Two\nlines\",cat\n\nFruits,apple\n";
+
+        let rows = parse_games(text).unwrap();
+
+        assert_eq!(
+            rows.iter().map(|(line, _)| *line).collect::<Vec<_>>(),
+            vec![3, 4, 7]
+        );
+    }
+
+    #[test]
+    fn test_parse_games_invalid_header() {
+        assert_eq!(parse_games("").unwrap_err(), "The CSV is empty");
+        assert_eq!(parse_games("name\nx").unwrap_err(), "Missing column words");
+        assert_eq!(
+            parse_games("name,words,owner\n").unwrap_err(),
+            "Unknown column owner"
+        );
+        assert!(
+            parse_games("name,words,available_to\nx,abc,tomorrow").unwrap()[0]
+                .1
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! Lifecycle of a game
+//!
+//! The status is derived from the `published` and `archived` flags and the availability window.
+//! `GameStatus::new` and `status_filter` are the only places that interpret those columns,
+//! resolvers should gate access through them.
+use crate::schema::games;
+use chrono::{DateTime, Utc};
+use diesel::expression::BoxableExpression;
+use diesel::pg::Pg;
+use diesel::prelude::*;
+use diesel::sql_types::Bool;
+
+#[derive(GraphQLEnum, Debug, Clone, Copy, PartialEq)]
+pub enum GameStatus {
+    /// Not published, only visible to the owner
+    Draft,
+    /// Published, but the availability window has not started yet
+    Scheduled,
+    /// Published and inside the availability window, accepts participations and solutions
+    Live,
+    /// Published and the availability window has passed
+    Ended,
+    /// Hidden from the game list, results stay readable
+    Archived,
+}
+
+impl GameStatus {
+    /// Statuses in which the game and its results are readable by anyone
+    pub const PUBLIC: [GameStatus; 3] = [GameStatus::Live, GameStatus::Ended, GameStatus::Archived];
+
+    /// A published game without `available_from` is never started
+    pub fn new(
+        published: bool,
+        archived: bool,
+        available_from: Option<DateTime<Utc>>,
+        available_to: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        if archived {
+            return GameStatus::Archived;
+        }
+        if !published {
+            return GameStatus::Draft;
+        }
+        match (available_from, available_to) {
+            (None, _) => GameStatus::Scheduled,
+            (Some(from), _) if now < from => GameStatus::Scheduled,
+            (_, Some(to)) if to <= now => GameStatus::Ended,
+            _ => GameStatus::Live,
+        }
+    }
+
+    /// Error to report when the game is not `Live`
+    pub fn check_live(self) -> Result<(), &'static str> {
+        match self {
+            GameStatus::Live => Ok(()),
+            GameStatus::Draft | GameStatus::Scheduled => Err("Game not available"),
+            GameStatus::Ended => Err("Game has expired"),
+            GameStatus::Archived => Err("Game has been archived"),
+        }
+    }
+}
+
+/// Check that an availability window can be played
+/// A missing `available_from` is filled in when the game is published
+pub fn validate_window(
+    available_from: Option<DateTime<Utc>>,
+    available_to: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<(), &'static str> {
+    match (available_from, available_to) {
+        (Some(from), Some(to)) if to <= from => Err("availableTo must be later than availableFrom"),
+        (_, Some(to)) if to <= now => Err("availableTo must be in the future"),
+        _ => Ok(()),
+    }
+}
+
+type StatusFilter<QS> = Box<dyn BoxableExpression<QS, Pg, SqlType = Bool>>;
+
+/// SQL condition on the `games` table matching games in any of the given statuses
+/// Mirrors `GameStatus::new`
+pub fn status_filter<QS>(statuses: &[GameStatus], now: DateTime<Utc>) -> StatusFilter<QS>
+where
+    QS: 'static,
+    games::archived: SelectableExpression<QS>,
+    games::published: SelectableExpression<QS>,
+    games::available_from: SelectableExpression<QS>,
+    games::available_to: SelectableExpression<QS>,
+{
+    use self::games::dsl::{archived, available_from, available_to, published};
+
+    let mut filter: StatusFilter<QS> = Box::new(diesel::dsl::sql::<Bool>("FALSE"));
+    for status in statuses {
+        let condition: StatusFilter<QS> = match status {
+            GameStatus::Archived => Box::new(archived.eq(true)),
+            GameStatus::Draft => Box::new(archived.eq(false).and(published.eq(false))),
+            GameStatus::Scheduled => Box::new(
+                archived
+                    .eq(false)
+                    .and(published.eq(true))
+                    .and(available_from.is_null().or(available_from.gt(now))),
+            ),
+            GameStatus::Ended => Box::new(
+                archived
+                    .eq(false)
+                    .and(published.eq(true))
+                    .and(available_from.le(now))
+                    .and(available_to.le(now)),
+            ),
+            GameStatus::Live => Box::new(
+                archived
+                    .eq(false)
+                    .and(published.eq(true))
+                    .and(available_from.le(now))
+                    .and(available_to.is_null().or(available_to.gt(now))),
+            ),
+        };
+        filter = Box::new(filter.or(condition));
+    }
+    filter
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_status_follows_the_lifecycle() {
+        let now = Utc::now();
+        let from = Some(now - Duration::hours(1));
+        let to = Some(now + Duration::hours(1));
+
+        assert_eq!(
+            GameStatus::new(false, false, from, to, now),
+            GameStatus::Draft
+        );
+        assert_eq!(
+            GameStatus::new(true, false, to, None, now),
+            GameStatus::Scheduled
+        );
+        assert_eq!(
+            GameStatus::new(true, false, from, to, now),
+            GameStatus::Live
+        );
+        assert_eq!(
+            GameStatus::new(true, false, from, None, now),
+            GameStatus::Live
+        );
+        assert_eq!(
+            GameStatus::new(true, false, from, from, now),
+            GameStatus::Ended
+        );
+        assert_eq!(
+            GameStatus::new(true, true, from, to, now),
+            GameStatus::Archived
+        );
+    }
+
+    #[test]
+    fn test_published_game_without_start_is_scheduled() {
+        let now = Utc::now();
+
+        assert_eq!(
+            GameStatus::new(true, false, None, None, now),
+            GameStatus::Scheduled
+        );
+        assert!(GameStatus::Scheduled.check_live().is_err());
+    }
+
+    #[test]
+    fn test_validate_window() {
+        let now = Utc::now();
+        let earlier = Some(now - Duration::hours(1));
+        let later = Some(now + Duration::hours(1));
+
+        assert!(validate_window(None, None, now).is_ok());
+        assert!(validate_window(earlier, later, now).is_ok());
+        assert!(validate_window(None, later, now).is_ok());
+        assert!(validate_window(later, earlier, now).is_err());
+        assert!(validate_window(later, later, now).is_err());
+        assert!(validate_window(None, earlier, now).is_err());
+    }
+
+    #[test]
+    fn test_window_bounds() {
+        let now = Utc::now();
+
+        assert_eq!(
+            GameStatus::new(true, false, Some(now), None, now),
+            GameStatus::Live
+        );
+        assert_eq!(
+            GameStatus::new(true, false, Some(now), Some(now), now),
+            GameStatus::Ended
+        );
+    }
+}
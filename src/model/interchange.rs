@@ -0,0 +1,403 @@
+//! Versioned interchange format for moving puzzles between instances and tools
+//!
+//! A document is a JSON object of the following shape:
+//!
+//! ```json
+//! {
+//!     "format": "perplexio-puzzle",
+//!     "version": 1,
+//!     "game": {
+//!         "name": "Fruits",
+//!         "owner": "Daniel",
+//!         "availableFrom": "2019-06-01T10:00:00Z",
+//!         "availableTo": null
+//!     },
+//!     "puzzle": {
+//!         "columns": 3,
+//!         "rows": 2,
+//!         "grid": ["fig", "xyz"],
+//!         "words": ["fig"],
+//!         "placements": [{ "word": "fig", "start": { "x": 0, "y": 0 }, "end": { "x": 2, "y": 0 } }],
+//!         "seed": 42
+//!     }
+//! }
+//! ```
+//!
+//! - `game` is optional, tools exchanging bare puzzles may omit it
+//! - `grid` holds the rows of the table from top to bottom
+//! - `placements` hold one entry per word, reading the grid from `start` to `end` spells `word`
+//! - `seed` is optional, it is only meaningful for puzzles generated by Perplexio
+//!
+//! Documents with a higher `version` than `FORMAT_VERSION` are rejected.
+use super::puzzle::Puzzle;
+use super::vector::Vector;
+use chrono::{DateTime, Utc};
+use serde_json::Value as JsonValue;
+use std::collections::HashSet;
+use std::fmt;
+
+pub const FORMAT_NAME: &str = "perplexio-puzzle";
+pub const FORMAT_VERSION: u32 = 1;
+
+const IPUZ_VERSION: &str = "http://ipuz.org/v2";
+const IPUZ_KIND: &str = "http://ipuz.org/wordsearch#1";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PuzzleDocument {
+    pub format: String,
+    pub version: u32,
+    #[serde(default)]
+    pub game: Option<GameMetadata>,
+    pub puzzle: PuzzleData,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GameMetadata {
+    pub name: String,
+    #[serde(default)]
+    pub owner: Option<String>,
+    #[serde(default)]
+    pub available_from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub available_to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// ISO 639-1 code of the language of the words
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub difficulty: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PuzzleData {
+    pub columns: usize,
+    pub rows: usize,
+    pub grid: Vec<String>,
+    pub words: Vec<String>,
+    pub placements: Vec<Placement>,
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Placement {
+    pub word: String,
+    pub start: Vector,
+    pub end: Vector,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ImportError {
+    Malformed(String),
+    UnsupportedFormat(String),
+    UnsupportedVersion(u32),
+    InvalidGrid(String),
+    InvalidPlacement(String),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImportError::Malformed(e) => write!(formatter, "Malformed document: {}", e),
+            ImportError::UnsupportedFormat(f) => write!(formatter, "Unsupported format: {}", f),
+            ImportError::UnsupportedVersion(v) => {
+                write!(formatter, "Unsupported format version: {}", v)
+            }
+            ImportError::InvalidGrid(e) => write!(formatter, "Invalid grid: {}", e),
+            ImportError::InvalidPlacement(e) => write!(formatter, "Invalid placement: {}", e),
+        }
+    }
+}
+
+impl PuzzleDocument {
+    pub fn new(puzzle: &Puzzle, game: Option<GameMetadata>) -> Self {
+        let (columns, rows) = puzzle.get_shape();
+        Self {
+            format: FORMAT_NAME.to_string(),
+            version: FORMAT_VERSION,
+            game: game,
+            puzzle: PuzzleData {
+                columns: columns,
+                rows: rows,
+                grid: puzzle.render_table(),
+                words: puzzle.get_words().clone(),
+                placements: placements(puzzle),
+                seed: puzzle.get_seed(),
+            },
+        }
+    }
+
+    pub fn from_json(document: &str) -> Result<Self, ImportError> {
+        let document: Self =
+            serde_json::from_str(document).map_err(|e| ImportError::Malformed(e.to_string()))?;
+        document.check_format()?;
+        Ok(document)
+    }
+
+    /// Reject documents of other formats and newer versions
+    pub fn check_format(&self) -> Result<(), ImportError> {
+        if self.format != FORMAT_NAME {
+            return Err(ImportError::UnsupportedFormat(self.format.clone()));
+        }
+        if self.version == 0 || self.version > FORMAT_VERSION {
+            return Err(ImportError::UnsupportedVersion(self.version));
+        }
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        serde_json::to_value(self).expect("Failed to serialize puzzle document")
+    }
+
+    /// Validate the document and turn it back into a `Puzzle`
+    pub fn to_puzzle(&self) -> Result<Puzzle, ImportError> {
+        let data = &self.puzzle;
+        if data.columns == 0 || data.rows == 0 {
+            return Err(ImportError::InvalidGrid("grid must not be empty".into()));
+        }
+        if data.grid.len() != data.rows {
+            return Err(ImportError::InvalidGrid(format!(
+                "expected {} rows, found {}",
+                data.rows,
+                data.grid.len()
+            )));
+        }
+        if let Some(row) = data.grid.iter().find(|r| r.chars().count() != data.columns) {
+            return Err(ImportError::InvalidGrid(format!(
+                "row '{}' is not {} characters long",
+                row, data.columns
+            )));
+        }
+
+        let mut unplaced = data.words.clone();
+        let mut solutions = HashSet::with_capacity(data.placements.len());
+        for placement in data.placements.iter() {
+            let spelled = read_segment(&data.grid, data.columns, data.rows, placement)?;
+            if spelled != placement.word {
+                return Err(ImportError::InvalidPlacement(format!(
+                    "expected '{}' but the grid spells '{}'",
+                    placement.word, spelled
+                )));
+            }
+            let index = unplaced
+                .iter()
+                .position(|w| *w == placement.word)
+                .ok_or_else(|| {
+                    ImportError::InvalidPlacement(format!(
+                        "'{}' is not in the word list",
+                        placement.word
+                    ))
+                })?;
+            unplaced.swap_remove(index);
+            solutions.insert(normalise(placement.start, placement.end));
+        }
+        if let Some(word) = unplaced.first() {
+            return Err(ImportError::InvalidPlacement(format!(
+                "'{}' has no placement",
+                word
+            )));
+        }
+
+        Ok(Puzzle::from_table(
+            data.grid.clone(),
+            data.columns,
+            data.rows,
+            solutions,
+            data.words.clone(),
+            data.seed,
+        ))
+    }
+}
+
+/// Render the puzzle as an [ipuz](http://ipuz.org) word search
+/// Placements are stored in the `com.perplexio:placements` extension field
+pub fn to_ipuz(puzzle: &Puzzle, game: Option<&GameMetadata>) -> JsonValue {
+    let (columns, rows) = puzzle.get_shape();
+    let grid = puzzle
+        .render_table()
+        .iter()
+        .map(|row| row.chars().map(|c| c.to_string()).collect())
+        .collect::<Vec<Vec<String>>>();
+    let mut result = json!({
+        "version": IPUZ_VERSION,
+        "kind": [IPUZ_KIND],
+        "dimensions": { "width": columns, "height": rows },
+        "puzzle": grid,
+        "solution": puzzle.get_words(),
+        "com.perplexio:placements": placements(puzzle),
+    });
+    if let Some(game) = game {
+        result["title"] = json!(game.name);
+        if let Some(owner) = game.owner.as_ref() {
+            result["author"] = json!(owner);
+        }
+    }
+    result
+}
+
+/// Match every solution of the puzzle to the word it spells
+/// Placements follow the order of the word list
+fn placements(puzzle: &Puzzle) -> Vec<Placement> {
+    let grid = puzzle.render_table();
+    let (columns, rows) = puzzle.get_shape();
+    let mut solutions = puzzle.get_solutions().iter().cloned().collect::<Vec<_>>();
+    solutions.sort_by_key(|(a, b)| (a.x, a.y, b.x, b.y));
+
+    let mut result = Vec::with_capacity(solutions.len());
+    for word in puzzle.get_words() {
+        let found = solutions.iter().enumerate().find_map(|(i, (a, b))| {
+            let forward = Placement {
+                word: word.clone(),
+                start: *a,
+                end: *b,
+            };
+            let backward = Placement {
+                word: word.clone(),
+                start: *b,
+                end: *a,
+            };
+            vec![forward, backward]
+                .into_iter()
+                .find(|p| read_segment(&grid, columns, rows, p).ok().as_ref() == Some(word))
+                .map(|p| (i, p))
+        });
+        if let Some((i, placement)) = found {
+            solutions.remove(i);
+            result.push(placement);
+        }
+    }
+    result
+}
+
+fn read_segment(
+    grid: &[String],
+    columns: usize,
+    rows: usize,
+    placement: &Placement,
+) -> Result<String, ImportError> {
+    let diff = placement.end - placement.start;
+    if diff.x != 0 && diff.y != 0 && diff.x.abs() != diff.y.abs() {
+        return Err(ImportError::InvalidPlacement(format!(
+            "'{}' is not horizontal, vertical or diagonal",
+            placement.word
+        )));
+    }
+    let in_bounds =
+        |v: Vector| v.x >= 0 && v.y >= 0 && (v.x as usize) < columns && (v.y as usize) < rows;
+    if !in_bounds(placement.start) || !in_bounds(placement.end) {
+        return Err(ImportError::InvalidPlacement(format!(
+            "'{}' is outside of the grid",
+            placement.word
+        )));
+    }
+    let len = diff.x.abs().max(diff.y.abs()) + 1;
+    let dir = diff.normal();
+    let mut current = placement.start;
+    let mut result = String::with_capacity(len as usize);
+    for _ in 0..len {
+        let row = &grid[current.y as usize];
+        result.extend(row.chars().nth(current.x as usize));
+        current = current + dir;
+    }
+    Ok(result)
+}
+
+/// Solutions are stored with the left-most, then top-most endpoint first
+fn normalise(a: Vector, b: Vector) -> (Vector, Vector) {
+    if a.x < b.x || (a.x == b.x && a.y <= b.y) {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn words() -> Vec<String> {
+        ["almafa", "korte", "krumpli", "bejgli", "korte"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip_reproduces_puzzle() {
+        let puzzle = Puzzle::from_words(words(), 1000).expect("Failed to generate");
+        let game = GameMetadata {
+            name: "fruits".to_string(),
+            owner: Some("snorrwe".to_string()),
+            available_from: Some(Utc::now()),
+            available_to: None,
+            description: Some("Fruits of the garden".to_string()),
+            tags: vec!["fruits".to_string()],
+            language: Some("hu".to_string()),
+            difficulty: Some("easy".to_string()),
+        };
+        let document = PuzzleDocument::new(&puzzle, Some(game.clone()));
+        let json = document.to_json().to_string();
+
+        let imported = PuzzleDocument::from_json(&json).expect("Failed to parse");
+        assert_eq!(imported, document);
+        assert_eq!(imported.game, Some(game));
+
+        let imported = imported.to_puzzle().expect("Failed to import");
+        assert_eq!(imported, puzzle);
+    }
+
+    #[test]
+    fn test_placements_spell_words() {
+        let puzzle = Puzzle::from_words(words(), 1000).expect("Failed to generate");
+        let document = PuzzleDocument::new(&puzzle, None);
+
+        assert_eq!(document.puzzle.placements.len(), words().len());
+        for (placement, word) in document.puzzle.placements.iter().zip(words().iter()) {
+            assert_eq!(&placement.word, word);
+        }
+    }
+
+    #[test]
+    fn test_rejects_unknown_version() {
+        let puzzle = Puzzle::from_words(words(), 1000).expect("Failed to generate");
+        let mut document = PuzzleDocument::new(&puzzle, None);
+        document.version = FORMAT_VERSION + 1;
+
+        let result = PuzzleDocument::from_json(&document.to_json().to_string());
+        assert_eq!(
+            result,
+            Err(ImportError::UnsupportedVersion(FORMAT_VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn test_rejects_placement_not_matching_grid() {
+        let puzzle = Puzzle::from_words(words(), 1000).expect("Failed to generate");
+        let mut document = PuzzleDocument::new(&puzzle, None);
+        let placement = &mut document.puzzle.placements[0];
+        std::mem::swap(&mut placement.start, &mut placement.end);
+
+        match document.to_puzzle() {
+            Err(ImportError::InvalidPlacement(_)) => {}
+            r => panic!("Expected invalid placement, got {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_ipuz_export() {
+        let puzzle = Puzzle::from_words(words(), 1000).expect("Failed to generate");
+        let (columns, rows) = puzzle.get_shape();
+        let ipuz = to_ipuz(&puzzle, None);
+
+        assert_eq!(ipuz["kind"][0], IPUZ_KIND);
+        assert_eq!(ipuz["dimensions"]["width"], columns);
+        assert_eq!(ipuz["puzzle"].as_array().unwrap().len(), rows);
+        assert_eq!(ipuz["solution"], json!(words()));
+    }
+}
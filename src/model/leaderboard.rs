@@ -0,0 +1,33 @@
+//! Paging of the leaderboards, the players are ranked in SQL by `graphql::leaderboards`
+
+pub const DEFAULT_PER_PAGE: usize = 25;
+pub const MAX_PER_PAGE: usize = 100;
+
+pub fn validate_paging(
+    page: Option<i32>,
+    per_page: Option<i32>,
+) -> Result<(usize, usize), &'static str> {
+    let page = page.unwrap_or(0);
+    let per_page = per_page.unwrap_or(DEFAULT_PER_PAGE as i32);
+    if page < 0 {
+        return Err("page must not be negative");
+    }
+    if per_page < 1 || per_page as usize > MAX_PER_PAGE {
+        return Err("perPage must be between 1 and 100");
+    }
+    Ok((page as usize, per_page as usize))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_validate_paging() {
+        assert_eq!(validate_paging(None, None), Ok((0, DEFAULT_PER_PAGE)));
+        assert_eq!(validate_paging(Some(2), Some(10)), Ok((2, 10)));
+        assert!(validate_paging(Some(-1), None).is_err());
+        assert!(validate_paging(None, Some(0)).is_err());
+        assert!(validate_paging(None, Some(101)).is_err());
+    }
+}
@@ -0,0 +1,83 @@
+//! Descriptive metadata of games used for listing and filtering
+
+/// Trim and lowercase the tags, dropping empty ones and duplicates
+pub fn normalise_tags(tags: Vec<String>) -> Vec<String> {
+    let mut result: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if !tag.is_empty() && !result.contains(&tag) {
+            result.push(tag);
+        }
+    }
+    result
+}
+
+/// Languages are stored as lowercase ISO 639-1 codes, e.g. `en`
+pub fn normalise_language(language: &str) -> Result<String, &'static str> {
+    let language = language.trim().to_lowercase();
+    if language.len() == 2 && language.chars().all(|c| c.is_ascii_lowercase()) {
+        Ok(language)
+    } else {
+        Err("Language must be a two letter ISO 639-1 code")
+    }
+}
+
+/// Name a new game after `name` without colliding with the `taken` names of the owner
+/// The label marks where the game came from, e.g. `Fruits (copy)`, `Fruits (copy 2)`
+pub fn unique_name(name: &str, label: &str, taken: &[String]) -> String {
+    (1..)
+        .map(|i| match i {
+            1 => format!("{} ({})", name, label),
+            _ => format!("{} ({} {})", name, label, i),
+        })
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap()
+}
+
+/// Escape the wildcards of `LIKE` patterns, so the text only matches itself
+pub fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_normalise_tags() {
+        let tags = ["Animals", " animals ", "", "Kids"]
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+
+        assert_eq!(normalise_tags(tags), vec!["animals", "kids"]);
+    }
+
+    #[test]
+    fn test_normalise_language() {
+        assert_eq!(normalise_language(" EN ").unwrap(), "en");
+        assert!(normalise_language("eng").is_err());
+        assert!(normalise_language("e1").is_err());
+    }
+
+    #[test]
+    fn test_unique_name() {
+        let taken = vec!["Fruits (copy)".to_string(), "Fruits (copy 2)".to_string()];
+
+        assert_eq!(unique_name("Fruits", "copy", &taken), "Fruits (copy 3)");
+        assert_eq!(
+            unique_name("Fruits", "imported", &taken),
+            "Fruits (imported)"
+        );
+    }
+
+    #[test]
+    fn test_escape_like() {
+        assert_eq!(escape_like("Fruits"), "Fruits");
+        assert_eq!(escape_like("100% fruit_salad"), "100\\% fruit\\_salad");
+        assert_eq!(escape_like("a\\b"), "a\\\\b");
+        assert_eq!(escape_like("\\%"), "\\\\\\%");
+    }
+}
@@ -1,6 +1,15 @@
+pub mod bundle;
+pub mod dictionary;
+pub mod extraction;
+pub mod game_csv;
+pub mod game_status;
+pub mod interchange;
+pub mod leaderboard;
+pub mod metadata;
 pub mod paginated;
 pub mod participation;
 pub mod puzzle;
+pub mod schedule;
 pub mod solution;
 pub mod user;
 pub mod vector;
@@ -40,5 +49,4 @@ pub mod datetime_format {
             .map_err(serde::de::Error::custom)
             .map(|date| Some(date))
     }
-
 }
@@ -1,10 +1,9 @@
-use super::super::schema::game_participations;
+use super::super::schema::{game_participations, participation_intervals};
 use crate::DieselConnection;
-use crate::model::user::User;
-use chrono::{DateTime, Utc};
+use crate::entity::game_entities::ParticipationOutcome;
+use chrono::{DateTime, Duration, Utc};
 use diesel::prelude::*;
-use diesel::update;
-use juniper::FieldResult;
+use diesel::{insert_into, update};
 
 #[derive(Insertable)]
 #[table_name = "game_participations"]
@@ -13,6 +12,7 @@ pub struct GameParticipation {
     pub game_id: i32,
     pub start_time: DateTime<Utc>,
     pub end_time: Option<DateTime<Utc>>,
+    pub puzzle_version_id: i32,
 }
 
 #[derive(Serialize, Queryable)]
@@ -33,6 +33,27 @@ pub struct GameParticipationEntity {
     pub start_time: DateTime<Utc>,
     pub end_time: Option<DateTime<Utc>>,
     pub duration: Option<i32>,
+    /// The version of the puzzle the participation was played on
+    pub puzzle_version_id: i32,
+    /// `None` while the participation is in progress
+    pub outcome: Option<ParticipationOutcome>,
+}
+
+/// A stretch of time the participant played, `end_time` is `None` until the next pause
+#[derive(Queryable)]
+pub struct ParticipationIntervalEntity {
+    pub id: i32,
+    pub participation_id: i32,
+    pub start_time: DateTime<Utc>,
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+#[derive(Insertable)]
+#[table_name = "participation_intervals"]
+pub struct ParticipationIntervalInsert {
+    pub participation_id: i32,
+    pub start_time: DateTime<Utc>,
+    pub end_time: Option<DateTime<Utc>>,
 }
 
 impl GameParticipationEntity {
@@ -47,28 +68,344 @@ impl GameParticipationEntity {
     }
 }
 
+/// Close the participation in progress at `now`, its duration is the play time in milliseconds
 pub fn end_participation(
     connection: &DieselConnection,
-    user: &User,
+    participation: &GameParticipationEntity,
+    outcome: ParticipationOutcome,
+    now: DateTime<Utc>,
+) -> QueryResult<usize> {
+    use crate::schema::game_participations::dsl as gp;
+
+    connection.transaction(|| {
+        let intervals = load_intervals(connection, vec![participation.id])?;
+        let duration = play_time(participation, &intervals, now).num_milliseconds();
+        close_interval(connection, participation.id, now)?;
+        update(
+            gp::game_participations
+                .find(participation.id)
+                .filter(gp::end_time.is_null()),
+        )
+        .set((
+            gp::end_time.eq(now),
+            gp::duration.eq(duration as i32),
+            gp::outcome.eq(outcome),
+        ))
+        .execute(connection)
+    })
+}
+
+/// The play intervals of the participations, oldest first
+pub fn load_intervals(
+    connection: &DieselConnection,
+    participation_ids: Vec<i32>,
+) -> QueryResult<Vec<ParticipationIntervalEntity>> {
+    use crate::schema::participation_intervals::dsl as pi;
+
+    pi::participation_intervals
+        .filter(pi::participation_id.eq_any(participation_ids))
+        .order_by(pi::start_time)
+        .load(connection)
+}
+
+pub fn open_interval(
+    connection: &DieselConnection,
+    participation_id: i32,
+    now: DateTime<Utc>,
+) -> QueryResult<usize> {
+    use crate::schema::participation_intervals::dsl::participation_intervals;
+
+    insert_into(participation_intervals)
+        .values(ParticipationIntervalInsert {
+            participation_id: participation_id,
+            start_time: now,
+            end_time: None,
+        })
+        .execute(connection)
+}
+
+pub fn close_interval(
+    connection: &DieselConnection,
+    participation_id: i32,
+    now: DateTime<Utc>,
+) -> QueryResult<usize> {
+    use crate::schema::participation_intervals::dsl as pi;
+
+    update(
+        pi::participation_intervals.filter(
+            pi::participation_id
+                .eq(participation_id)
+                .and(pi::end_time.is_null()),
+        ),
+    )
+    .set(pi::end_time.eq(now))
+    .execute(connection)
+}
+
+/// Time played until `now`, the sum of the play intervals of the participation
+/// Participations started before the intervals were recorded count the wall clock time
+pub fn play_time(
+    participation: &GameParticipationEntity,
+    intervals: &[ParticipationIntervalEntity],
+    now: DateTime<Utc>,
+) -> Duration {
+    let intervals = intervals
+        .iter()
+        .filter(|i| i.participation_id == participation.id)
+        .collect::<Vec<_>>();
+    if intervals.is_empty() {
+        return participation.end_time.unwrap_or(now) - participation.start_time;
+    }
+    intervals
+        .iter()
+        .map(|i| i.end_time.unwrap_or(now) - i.start_time)
+        .fold(Duration::zero(), |total, played| total + played)
+}
+
+/// A participation in progress is paused while none of its play intervals is open
+pub fn is_paused(
+    participation: &GameParticipationEntity,
+    intervals: &[ParticipationIntervalEntity],
+) -> bool {
+    let mut intervals = intervals
+        .iter()
+        .filter(|i| i.participation_id == participation.id)
+        .peekable();
+    participation.end_time.is_none()
+        && intervals.peek().is_some()
+        && intervals.all(|i| i.end_time.is_some())
+}
+
+/// Players see every solution of the puzzle only after forfeiting it
+pub fn reveals_solutions(participation: Option<&GameParticipationEntity>) -> bool {
+    participation.and_then(|p| p.outcome) == Some(ParticipationOutcome::Forfeited)
+}
+
+/// Reject solutions of forfeited and paused participations
+/// Timed out participations are rejected by `service::time_limit::check_time_limit`
+pub fn check_accepts_solutions(
+    participation: &GameParticipationEntity,
+    intervals: &[ParticipationIntervalEntity],
+) -> Result<(), &'static str> {
+    if participation.outcome == Some(ParticipationOutcome::Forfeited) {
+        return Err("The participation was forfeited");
+    }
+    if is_paused(participation, intervals) {
+        return Err("The participation is paused");
+    }
+    Ok(())
+}
+
+/// The participation of the user on the current puzzle of the game
+pub fn current_participation(
+    connection: &DieselConnection,
     game_id: i32,
-) -> FieldResult<bool> {
-    use crate::schema::game_participations::dsl::{
-        duration, end_time as et, game_id as gid, game_participations as gp, start_time as st,
-        user_id,
-    };
-
-    let end_time = Utc::now();
-    let start_time = gp
-        .filter(user_id.eq(user.id).and(gid.eq(game_id)))
-        .select((st,))
-        .get_result::<(DateTime<Utc>,)>(connection)
-        .optional()?
-        .ok_or("User is not participating")?;
-
-    let dur = (end_time - start_time.0).num_milliseconds();
-    update(gp.filter(user_id.eq(user.id).and(gid.eq(game_id))))
-        .set((et.eq(end_time), duration.eq(dur as i32)))
-        .execute(connection)?;
-
-    Ok(true)
+    user_id: i32,
+) -> QueryResult<Option<GameParticipationEntity>> {
+    use crate::schema::game_participations::dsl as gp;
+    use crate::schema::puzzles::dsl as p;
+
+    gp::game_participations
+        .filter(
+            gp::game_id.eq(game_id).and(gp::user_id.eq(user_id)).and(
+                gp::puzzle_version_id
+                    .nullable()
+                    .eq_any(p::puzzles.select(p::version_id)),
+            ),
+        )
+        .get_result::<GameParticipationEntity>(connection)
+        .optional()
+}
+
+/// Longest time limit of a game in seconds, a day
+const MAX_TIME_LIMIT: i32 = 24 * 60 * 60;
+
+pub fn validate_time_limit(time_limit: Option<i32>) -> Result<(), &'static str> {
+    match time_limit {
+        Some(limit) if !(1..=MAX_TIME_LIMIT).contains(&limit) => {
+            Err("timeLimit must be between 1 second and a day")
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The moment a participation started at `start_time` runs out of the time limit in seconds
+pub fn deadline(start_time: DateTime<Utc>, time_limit: i32) -> DateTime<Utc> {
+    start_time + Duration::seconds(time_limit as i64)
+}
+
+/// Whether a participation still in progress exceeded the time limit of its game
+pub fn is_timed_out(
+    participation: &GameParticipationEntity,
+    time_limit: Option<i32>,
+    now: DateTime<Utc>,
+) -> bool {
+    match time_limit {
+        Some(limit) => {
+            participation.end_time.is_none() && deadline(participation.start_time, limit) <= now
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn participation(end_time: Option<DateTime<Utc>>) -> GameParticipationEntity {
+        GameParticipationEntity {
+            id: 1,
+            user_id: 1,
+            game_id: 1,
+            start_time: Utc.ymd(2019, 8, 18).and_hms(10, 0, 0),
+            end_time: end_time,
+            duration: None,
+            puzzle_version_id: 1,
+            outcome: None,
+        }
+    }
+
+    #[test]
+    fn test_is_timed_out() {
+        let start = Utc.ymd(2019, 8, 18).and_hms(10, 0, 0);
+        let playing = participation(None);
+
+        assert!(!is_timed_out(&playing, None, start + Duration::days(7)));
+        assert!(!is_timed_out(
+            &playing,
+            Some(60),
+            start + Duration::seconds(59)
+        ));
+        assert!(is_timed_out(
+            &playing,
+            Some(60),
+            start + Duration::seconds(60)
+        ));
+    }
+
+    #[test]
+    fn test_ended_participations_do_not_time_out() {
+        let start = Utc.ymd(2019, 8, 18).and_hms(10, 0, 0);
+        let ended = participation(Some(start + Duration::seconds(30)));
+
+        assert!(!is_timed_out(
+            &ended,
+            Some(60),
+            start + Duration::seconds(90)
+        ));
+    }
+
+    fn interval(
+        participation_id: i32,
+        start: DateTime<Utc>,
+        end: Option<DateTime<Utc>>,
+    ) -> ParticipationIntervalEntity {
+        ParticipationIntervalEntity {
+            id: 1,
+            participation_id: participation_id,
+            start_time: start,
+            end_time: end,
+        }
+    }
+
+    #[test]
+    fn test_play_time_sums_the_intervals() {
+        let start = Utc.ymd(2019, 8, 18).and_hms(10, 0, 0);
+        let playing = participation(None);
+        let intervals = vec![
+            interval(1, start, Some(start + Duration::minutes(5))),
+            interval(2, start, Some(start + Duration::hours(1))),
+            interval(1, start + Duration::hours(2), None),
+        ];
+        let now = start + Duration::hours(2) + Duration::minutes(3);
+
+        assert_eq!(play_time(&playing, &intervals, now), Duration::minutes(8));
+    }
+
+    #[test]
+    fn test_play_time_without_intervals_is_wall_clock() {
+        let start = Utc.ymd(2019, 8, 18).and_hms(10, 0, 0);
+        let ended = participation(Some(start + Duration::minutes(30)));
+
+        assert_eq!(
+            play_time(&ended, &[], start + Duration::hours(1)),
+            Duration::minutes(30)
+        );
+        assert_eq!(
+            play_time(&participation(None), &[], start + Duration::hours(1)),
+            Duration::hours(1)
+        );
+    }
+
+    #[test]
+    fn test_is_paused() {
+        let start = Utc.ymd(2019, 8, 18).and_hms(10, 0, 0);
+        let playing = participation(None);
+        let paused = vec![interval(1, start, Some(start + Duration::minutes(5)))];
+        let resumed = vec![
+            interval(1, start, Some(start + Duration::minutes(5))),
+            interval(1, start + Duration::minutes(10), None),
+        ];
+
+        assert!(!is_paused(&playing, &[]));
+        assert!(is_paused(&playing, &paused));
+        assert!(!is_paused(&playing, &resumed));
+        assert!(!is_paused(
+            &participation(Some(start + Duration::minutes(5))),
+            &paused
+        ));
+    }
+
+    #[test]
+    fn test_only_forfeits_reveal_solutions() {
+        let start = Utc.ymd(2019, 8, 18).and_hms(10, 0, 0);
+        let outcome = |outcome| GameParticipationEntity {
+            end_time: Some(start + Duration::minutes(5)),
+            outcome: Some(outcome),
+            ..participation(None)
+        };
+
+        assert!(reveals_solutions(Some(&outcome(
+            ParticipationOutcome::Forfeited
+        ))));
+        assert!(!reveals_solutions(Some(&outcome(
+            ParticipationOutcome::Completed
+        ))));
+        assert!(!reveals_solutions(Some(&outcome(
+            ParticipationOutcome::TimedOut
+        ))));
+        assert!(!reveals_solutions(Some(&participation(None))));
+        assert!(!reveals_solutions(None));
+    }
+
+    #[test]
+    fn test_check_accepts_solutions() {
+        let start = Utc.ymd(2019, 8, 18).and_hms(10, 0, 0);
+        let playing = participation(None);
+        let forfeited = GameParticipationEntity {
+            end_time: Some(start + Duration::minutes(5)),
+            outcome: Some(ParticipationOutcome::Forfeited),
+            ..participation(None)
+        };
+        let paused = vec![interval(1, start, Some(start + Duration::minutes(5)))];
+
+        assert_eq!(check_accepts_solutions(&playing, &[]), Ok(()));
+        assert_eq!(
+            check_accepts_solutions(&forfeited, &[]),
+            Err("The participation was forfeited")
+        );
+        assert_eq!(
+            check_accepts_solutions(&playing, &paused),
+            Err("The participation is paused")
+        );
+    }
+
+    #[test]
+    fn test_validate_time_limit() {
+        assert!(validate_time_limit(None).is_ok());
+        assert!(validate_time_limit(Some(300)).is_ok());
+        assert!(validate_time_limit(Some(0)).is_err());
+        assert!(validate_time_limit(Some(MAX_TIME_LIMIT + 1)).is_err());
+    }
 }
@@ -1,11 +1,13 @@
 use super::vector::{segments_intersecting, Vector};
 use crate::entity::puzzle_entities::PuzzleEntity;
 use rand::prelude::*;
+use rand::rngs::StdRng;
 use serde_json::Value as JsonValue;
 use std::collections::HashSet;
 use std::fmt;
+use std::time::Instant;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Puzzle {
     table: Vec<char>,
@@ -13,6 +15,10 @@ pub struct Puzzle {
     rows: usize,
     solutions: HashSet<(Vector, Vector)>,
     words: Vec<String>,
+    /// Seed the puzzle was generated with, if known
+    /// Generating from the same words with the same seed yields the same puzzle
+    #[serde(default)]
+    seed: Option<u64>,
 }
 
 impl From<PuzzleEntity> for Puzzle {
@@ -35,14 +41,89 @@ impl From<PuzzleEntity> for Puzzle {
             rows: puzzle.table_rows as usize,
             words: puzzle.words,
             solutions: solutions,
+            seed: puzzle.seed.map(|s| s as u64),
         }
     }
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, PartialEq)]
 pub enum PuzzleError {
     CantFit,
     InvalidArgument,
+    Timeout,
+}
+
+impl fmt::Display for PuzzleError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            PuzzleError::CantFit => "Failed to fit the words into a puzzle",
+            PuzzleError::InvalidArgument => "Invalid words",
+            PuzzleError::Timeout => "Puzzle generation ran out of time",
+        };
+        write!(formatter, "{}", message)
+    }
+}
+
+/// Parameters of puzzle generation
+/// e.g. `Generator::new(100).seed(42).generate(words)`
+pub struct Generator {
+    max_iterations: usize,
+    seed: Option<u64>,
+    deadline: Option<Instant>,
+}
+
+impl Generator {
+    pub fn new(max_iterations: usize) -> Self {
+        Self {
+            max_iterations: max_iterations,
+            seed: None,
+            deadline: None,
+        }
+    }
+
+    /// Use a deterministic seed instead of a random one
+    pub fn seed(self, seed: u64) -> Self {
+        Self {
+            seed: Some(seed),
+            ..self
+        }
+    }
+
+    /// Give up with `PuzzleError::Timeout` once the deadline has passed
+    pub fn deadline(self, deadline: Instant) -> Self {
+        Self {
+            deadline: Some(deadline),
+            ..self
+        }
+    }
+
+    pub fn generate(&self, words: Vec<String>) -> Result<Puzzle, PuzzleError> {
+        let seed = self.seed.unwrap_or_else(|| thread_rng().gen());
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut result = Err(PuzzleError::InvalidArgument);
+        for i in 0..self.max_iterations {
+            if self.deadline.map(|d| d <= Instant::now()).unwrap_or(false) {
+                return Err(PuzzleError::Timeout);
+            }
+            result = Puzzle::_from_words(&words, 10 + i, &mut rng).map(|mut puzzle| {
+                puzzle.seed = Some(seed);
+                puzzle
+            });
+            if let Ok(r) = result {
+                let (a, b) = if r.columns > r.rows {
+                    (r.columns, r.rows)
+                } else {
+                    (r.rows, r.columns)
+                };
+                let almost_square = a - b <= 2;
+                if almost_square {
+                    return Ok(r);
+                }
+            }
+            result = Err(PuzzleError::CantFit);
+        }
+        result
+    }
 }
 
 impl Puzzle {
@@ -60,6 +141,7 @@ impl Puzzle {
             rows: row,
             solutions: HashSet::new(),
             words: vec![],
+            seed: None,
         }
     }
 
@@ -70,6 +152,7 @@ impl Puzzle {
         row: usize,
         solutions: HashSet<(Vector, Vector)>,
         words: Vec<String>,
+        seed: Option<u64>,
     ) -> Puzzle {
         let table = table.iter().flat_map(|row| row.chars()).collect();
         Puzzle {
@@ -78,6 +161,7 @@ impl Puzzle {
             rows: row,
             solutions: solutions,
             words: words,
+            seed: seed,
         }
     }
 
@@ -123,6 +207,10 @@ impl Puzzle {
         &self.words
     }
 
+    pub fn get_seed(&self) -> Option<u64> {
+        self.seed
+    }
+
     /// Tables are matrices of characters
     /// They are column major
     /// so e.g.
@@ -161,34 +249,30 @@ impl Puzzle {
 
     /// Generate a new puzzle from the given words
     pub fn from_words(words: Vec<String>, max_iterations: usize) -> Result<Puzzle, PuzzleError> {
-        let mut result = Err(PuzzleError::InvalidArgument);
-        'a: for i in 0..max_iterations {
-            result = Self::_from_words(&words, 10 + i);
-            if let Ok(r) = result {
-                let (a, b) = if r.columns > r.rows {
-                    (r.columns, r.rows)
-                } else {
-                    (r.rows, r.columns)
-                };
-                let almost_square = a - b <= 2;
-                if almost_square {
-                    return Ok(r);
-                }
-            }
-            result = Err(PuzzleError::CantFit);
-        }
-        result
+        Generator::new(max_iterations).generate(words)
     }
 
-    fn _from_words(words: &Vec<String>, max_iterations: usize) -> Result<Puzzle, PuzzleError> {
+    /// Generate a new puzzle from the given words using a deterministic seed
+    pub fn from_words_seeded(
+        words: Vec<String>,
+        max_iterations: usize,
+        seed: u64,
+    ) -> Result<Puzzle, PuzzleError> {
+        Generator::new(max_iterations).seed(seed).generate(words)
+    }
+
+    fn _from_words<R: Rng>(
+        words: &Vec<String>,
+        max_iterations: usize,
+        rng: &mut R,
+    ) -> Result<Puzzle, PuzzleError> {
         assert!(words.len() > 0);
         let mut segments: Vec<(Vector, Vector)> = words
             .iter()
-            .map(|w| Self::random_segment_by_word(w))
+            .map(|w| Self::random_segment_by_word(w, rng))
             .collect();
         let mut intersections = vec![];
         intersections.reserve(words.len() * 2);
-        let mut rng = thread_rng();
         'a: for _ in 0..max_iterations {
             Self::intersections(&segments, &mut intersections);
             for intersection in intersections.iter() {
@@ -240,7 +324,7 @@ impl Puzzle {
                 result.solutions.insert(solution);
                 result.words.push(word.clone());
             });
-        result.fill_nulls();
+        result.fill_nulls(rng);
         Ok(result)
     }
 
@@ -286,7 +370,7 @@ impl Puzzle {
         }
     }
 
-    fn random_segment_by_word(word: &String) -> (Vector, Vector) {
+    fn random_segment_by_word<R: Rng>(word: &String, rng: &mut R) -> (Vector, Vector) {
         let dirs = [
             Vector::new(0, 1),
             Vector::new(0, -1),
@@ -297,7 +381,6 @@ impl Puzzle {
             Vector::new(-1, 1),
             Vector::new(-1, -1),
         ];
-        let mut rng = thread_rng();
         let dir = dirs[rng.gen_range(0, dirs.len())];
 
         let start = Vector::new(rng.gen_range(0, 5), rng.gen_range(0, 5));
@@ -306,8 +389,7 @@ impl Puzzle {
         (start, end)
     }
 
-    fn fill_nulls(&mut self) {
-        let mut rng = thread_rng();
+    fn fill_nulls<R: Rng>(&mut self, rng: &mut R) {
         for chr in self.table.iter_mut() {
             if *chr == '\0' {
                 *chr = rng.gen_range(b'a', b'z') as char;
@@ -341,6 +423,16 @@ impl fmt::Display for Puzzle {
     }
 }
 
+/// Whether both lists have the same words, in any order
+/// The puzzle of a game is only regenerated if its words change
+pub fn same_words(current: &[String], new: &[String]) -> bool {
+    let mut current = current.to_vec();
+    let mut new = new.to_vec();
+    current.sort();
+    new.sort();
+    current == new
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -382,6 +474,7 @@ mod test {
             rows: 2,
             solutions: HashSet::new(),
             words: vec![],
+            seed: None,
         };
 
         let rendered = puzzle.render_table();
@@ -408,5 +501,53 @@ mod test {
         assert!(shape.1 <= 4);
         assert!(shape.0 * shape.1 >= 4);
     }
-}
 
+    #[test]
+    fn test_same_seed_produces_same_puzzle() {
+        let words: Vec<String> = ["almafa", "korte", "krumpli", "bejgli"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        let a = Puzzle::from_words_seeded(words.clone(), 1000, 42).expect("Failed to generate");
+        let b = Puzzle::from_words_seeded(words, 1000, 42).expect("Failed to generate");
+
+        assert_eq!(a.get_seed(), Some(42));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_generation_stops_at_deadline() {
+        let words = ["almafa", "korte", "krumpli"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        let result = Generator::new(1000)
+            .deadline(Instant::now())
+            .generate(words);
+
+        assert_eq!(result, Err(PuzzleError::Timeout));
+    }
+
+    fn to_words(words: &[&str]) -> Vec<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn test_same_words_in_any_order() {
+        let current = to_words(&["almafa", "korte", "krumpli"]);
+
+        assert!(same_words(
+            &current,
+            &to_words(&["krumpli", "almafa", "korte"])
+        ));
+        assert!(!same_words(&current, &to_words(&["almafa", "korte"])));
+        assert!(!same_words(
+            &current,
+            &to_words(&["almafa", "korte", "bejgli"])
+        ));
+        assert!(!same_words(
+            &current,
+            &to_words(&["almafa", "korte", "krumpli", "korte"])
+        ));
+    }
+}
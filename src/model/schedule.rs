@@ -0,0 +1,126 @@
+//! Weekly recurring periods, e.g. every Monday 09:00 until Friday 17:00 in a given time zone
+use chrono::{
+    DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Period {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Recurrence {
+    pub time_zone: Tz,
+    pub start_day: Weekday,
+    pub start_time: NaiveTime,
+    pub end_day: Weekday,
+    pub end_time: NaiveTime,
+}
+
+impl Recurrence {
+    /// The first period starting at or after `after`
+    pub fn next_period(&self, after: DateTime<Utc>) -> Period {
+        let today = after.with_timezone(&self.time_zone).date().naive_local();
+        let (date, start) = (0..8)
+            .map(|offset| today + Duration::days(offset))
+            .filter(|date| date.weekday() == self.start_day)
+            .map(|date| (date, self.to_utc(date.and_time(self.start_time))))
+            .find(|(_, start)| *start >= after)
+            .expect("Every weekday occurs within 8 days");
+        let end_date = date + Duration::days(self.days_until_end());
+        Period {
+            start: start,
+            end: self.to_utc(end_date.and_time(self.end_time)),
+        }
+    }
+
+    /// Number of days between the start and the end of a period
+    /// A period ending on its starting day, but not later in the day, lasts until next week
+    fn days_until_end(&self) -> i64 {
+        let start = self.start_day.num_days_from_monday() as i64;
+        let end = self.end_day.num_days_from_monday() as i64;
+        match (end - start + 7) % 7 {
+            0 if self.end_time <= self.start_time => 7,
+            days => days,
+        }
+    }
+
+    /// Local times skipped by a daylight saving change are moved forward by an hour
+    fn to_utc(&self, local: NaiveDateTime) -> DateTime<Utc> {
+        match self.time_zone.from_local_datetime(&local).earliest() {
+            Some(time) => time.with_timezone(&Utc),
+            None => self.to_utc(local + Duration::hours(1)),
+        }
+    }
+
+    /// Local calendar date of the moment, used to name the games of a schedule
+    pub fn local_date(&self, time: DateTime<Utc>) -> NaiveDate {
+        time.with_timezone(&self.time_zone).date().naive_local()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn weekly_slot() -> Recurrence {
+        Recurrence {
+            time_zone: "Europe/Budapest".parse().unwrap(),
+            start_day: Weekday::Mon,
+            start_time: NaiveTime::from_hms(9, 0, 0),
+            end_day: Weekday::Fri,
+            end_time: NaiveTime::from_hms(17, 0, 0),
+        }
+    }
+
+    #[test]
+    fn test_next_period_starts_on_the_given_weekday() {
+        // Wednesday, 2019-07-10
+        let after = Utc.ymd(2019, 7, 10).and_hms(12, 0, 0);
+
+        let period = weekly_slot().next_period(after);
+
+        // Budapest is UTC+2 in the summer
+        assert_eq!(period.start, Utc.ymd(2019, 7, 15).and_hms(7, 0, 0));
+        assert_eq!(period.end, Utc.ymd(2019, 7, 19).and_hms(15, 0, 0));
+    }
+
+    #[test]
+    fn test_next_period_includes_the_start() {
+        let start = Utc.ymd(2019, 7, 15).and_hms(7, 0, 0);
+
+        assert_eq!(weekly_slot().next_period(start).start, start);
+        assert_eq!(
+            weekly_slot()
+                .next_period(start + Duration::seconds(1))
+                .start,
+            Utc.ymd(2019, 7, 22).and_hms(7, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_next_period_follows_daylight_saving() {
+        // Monday after the switch to winter time, Budapest is UTC+1
+        let after = Utc.ymd(2019, 10, 27).and_hms(12, 0, 0);
+
+        let period = weekly_slot().next_period(after);
+
+        assert_eq!(period.start, Utc.ymd(2019, 10, 28).and_hms(8, 0, 0));
+    }
+
+    #[test]
+    fn test_period_ending_on_the_starting_day_lasts_a_week() {
+        let recurrence = Recurrence {
+            end_day: Weekday::Mon,
+            end_time: NaiveTime::from_hms(8, 0, 0),
+            ..weekly_slot()
+        };
+        let after = Utc.ymd(2019, 7, 10).and_hms(12, 0, 0);
+
+        let period = recurrence.next_period(after);
+
+        assert_eq!(period.end - period.start, Duration::hours(7 * 24 - 1));
+    }
+}
@@ -34,6 +34,7 @@ pub struct SolutionForm {
     pub y1: i32,
     pub x2: i32,
     pub y2: i32,
+    pub puzzle_version_id: i32,
 }
 
 #[derive(Queryable)]
@@ -45,4 +46,47 @@ pub struct SolutionEntity {
     pub y1: i32,
     pub x2: i32,
     pub y2: i32,
+    pub puzzle_version_id: i32,
+}
+
+/// Whether `solution` is the last one of the puzzle the player has not found yet
+/// Solutions found before do not count again
+pub fn completes_puzzle(
+    found: &[SolutionDTO],
+    solution: &SolutionDTO,
+    puzzle_solutions: &[SolutionDTO],
+) -> bool {
+    !found.contains(solution) && found.len() + 1 == puzzle_solutions.len()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn solution(x: i32) -> SolutionDTO {
+        SolutionDTO::new(Vector::new(x, 0), Vector::new(x, 3))
+    }
+
+    #[test]
+    fn test_last_solution_completes_puzzle() {
+        let puzzle = vec![solution(0), solution(1), solution(2)];
+
+        assert!(completes_puzzle(
+            &[solution(0), solution(1)],
+            &solution(2),
+            &puzzle
+        ));
+        assert!(!completes_puzzle(&[solution(0)], &solution(1), &puzzle));
+    }
+
+    #[test]
+    fn test_duplicate_solution_does_not_complete_puzzle() {
+        let puzzle = vec![solution(0), solution(1), solution(2)];
+
+        assert!(!completes_puzzle(
+            &[solution(0), solution(1)],
+            &solution(1),
+            &puzzle
+        ));
+    }
 }
@@ -8,18 +8,24 @@ pub struct User {
 
 #[derive(Clone, Debug, GraphQLObject)]
 pub struct UserInfo {
+    /// Used to invite the user to private games
+    pub id: i32,
     pub name: String,
 }
 
 impl From<User> for UserInfo {
     fn from(u: User) -> Self {
-        Self { name: u.name }
+        Self {
+            id: u.id,
+            name: u.name,
+        }
     }
 }
 
 impl From<&User> for UserInfo {
     fn from(u: &User) -> Self {
         Self {
+            id: u.id,
             name: u.name.clone(),
         }
     }
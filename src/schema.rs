@@ -1,3 +1,43 @@
+table! {
+    daily_puzzles (date) {
+        date -> Date,
+        game_id -> Int4,
+        theme -> Varchar,
+    }
+}
+
+table! {
+    game_schedules (id) {
+        id -> Int4,
+        owner_id -> Int4,
+        name -> Varchar,
+        words -> Array<Text>,
+        words_per_game -> Int4,
+        time_zone -> Varchar,
+        start_day -> Varchar,
+        start_time -> Time,
+        end_day -> Varchar,
+        end_time -> Time,
+        next_start -> Timestamptz,
+        active -> Bool,
+    }
+}
+
+table! {
+    game_collaborators (game_id, user_id) {
+        game_id -> Int4,
+        user_id -> Int4,
+        role -> Varchar,
+    }
+}
+
+table! {
+    game_invitations (game_id, user_id) {
+        game_id -> Int4,
+        user_id -> Int4,
+    }
+}
+
 table! {
     game_participations (id) {
         id -> Int4,
@@ -6,6 +46,8 @@ table! {
         start_time -> Timestamptz,
         end_time -> Nullable<Timestamptz>,
         duration -> Nullable<Int4>,
+        puzzle_version_id -> Int4,
+        outcome -> Nullable<Varchar>,
     }
 }
 
@@ -17,6 +59,42 @@ table! {
         available_from -> Nullable<Timestamptz>,
         available_to -> Nullable<Timestamptz>,
         published -> Bool,
+        generation_status -> Varchar,
+        generation_error -> Nullable<Varchar>,
+        archived -> Bool,
+        schedule_id -> Nullable<Int4>,
+        description -> Nullable<Varchar>,
+        tags -> Array<Text>,
+        language -> Nullable<Varchar>,
+        difficulty -> Nullable<Varchar>,
+        visibility -> Varchar,
+        share_token -> Nullable<Varchar>,
+        time_limit -> Nullable<Int4>,
+    }
+}
+
+table! {
+    participation_intervals (id) {
+        id -> Int4,
+        participation_id -> Int4,
+        start_time -> Timestamptz,
+        end_time -> Nullable<Timestamptz>,
+    }
+}
+
+table! {
+    puzzle_versions (id) {
+        id -> Int4,
+        game_id -> Int4,
+        game_table -> Varchar,
+        table_columns -> Int4,
+        table_rows -> Int4,
+        solutions -> Array<Int4>,
+        words -> Array<Text>,
+        seed -> Nullable<Int8>,
+        created_at -> Timestamptz,
+        version -> Int4,
+        created_by -> Int4,
     }
 }
 
@@ -28,6 +106,8 @@ table! {
         table_rows -> Int4,
         solutions -> Array<Int4>,
         words -> Array<Text>,
+        seed -> Nullable<Int8>,
+        version_id -> Nullable<Int4>,
     }
 }
 
@@ -40,6 +120,7 @@ table! {
         y1 -> Int4,
         x2 -> Int4,
         y2 -> Int4,
+        puzzle_version_id -> Int4,
     }
 }
 
@@ -52,17 +133,57 @@ table! {
     }
 }
 
+table! {
+    word_bank_shares (word_bank_id, user_id) {
+        word_bank_id -> Int4,
+        user_id -> Int4,
+    }
+}
+
+table! {
+    word_banks (id) {
+        id -> Int4,
+        owner_id -> Int4,
+        name -> Varchar,
+        words -> Array<Text>,
+    }
+}
+
+joinable!(daily_puzzles -> games (game_id));
+joinable!(game_collaborators -> games (game_id));
+joinable!(game_collaborators -> users (user_id));
+joinable!(game_invitations -> games (game_id));
+joinable!(game_invitations -> users (user_id));
 joinable!(game_participations -> games (game_id));
+joinable!(game_participations -> puzzle_versions (puzzle_version_id));
 joinable!(game_participations -> users (user_id));
+joinable!(game_schedules -> users (owner_id));
+joinable!(games -> game_schedules (schedule_id));
 joinable!(games -> users (owner_id));
+joinable!(participation_intervals -> game_participations (participation_id));
+joinable!(puzzle_versions -> games (game_id));
+joinable!(puzzle_versions -> users (created_by));
 joinable!(puzzles -> games (game_id));
+joinable!(puzzles -> puzzle_versions (version_id));
 joinable!(solutions -> games (game_id));
+joinable!(solutions -> puzzle_versions (puzzle_version_id));
 joinable!(solutions -> users (user_id));
+joinable!(word_bank_shares -> users (user_id));
+joinable!(word_bank_shares -> word_banks (word_bank_id));
+joinable!(word_banks -> users (owner_id));
 
 allow_tables_to_appear_in_same_query!(
+    daily_puzzles,
+    game_collaborators,
+    game_invitations,
     game_participations,
+    game_schedules,
     games,
+    participation_intervals,
+    puzzle_versions,
     puzzles,
     solutions,
     users,
+    word_bank_shares,
+    word_banks,
 );
@@ -0,0 +1,195 @@
+//! Who can see and play a game, based on its visibility, and who can manage it
+//! Resolvers check access with `check_access`, listings only show `Public` games of other users
+//! Collaborators get the permissions of their role on the game, see `load_game`
+use crate::entity::game_entities::{CollaboratorRole, GameEntity, Visibility};
+use crate::model::user::User;
+use crate::schema::game_collaborators;
+use crate::DieselConnection;
+use diesel::dsl::{exists, select, Eq, Filter, Select};
+use diesel::prelude::*;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+
+const SHARE_TOKEN_LENGTH: usize = 24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// Publishing, visibility, invitations, collaborators and deletion
+    Own,
+    /// Editing drafts and regenerating puzzles
+    Edit,
+    /// Reading the participations and solutions
+    ReadResults,
+}
+
+impl Permission {
+    fn granted_to(self, role: CollaboratorRole) -> bool {
+        match self {
+            Permission::Own => false,
+            Permission::Edit => role == CollaboratorRole::Editor,
+            Permission::ReadResults => true,
+        }
+    }
+}
+
+pub fn is_owner(game: &GameEntity, user: Option<&User>) -> bool {
+    user.map(|u| u.id == game.owner_id).unwrap_or(false)
+}
+
+/// Unlisted games are accessible with the share token or once the user joined with it,
+/// private games only to invited users
+pub fn can_access(
+    connection: &DieselConnection,
+    game: &GameEntity,
+    user: Option<&User>,
+    share_token: Option<&str>,
+) -> QueryResult<bool> {
+    if is_owner(game, user) {
+        return Ok(true);
+    }
+    if let Some(user) = user {
+        if role_of(connection, game.id, user.id)?.is_some() {
+            return Ok(true);
+        }
+    }
+    match game.visibility {
+        Visibility::Public => Ok(true),
+        Visibility::Unlisted => {
+            if share_token.is_some() && share_token == game.share_token.as_deref() {
+                return Ok(true);
+            }
+            match user {
+                Some(user) => is_participating(connection, game.id, user.id),
+                None => Ok(false),
+            }
+        }
+        Visibility::Private => match user {
+            Some(user) => is_invited(connection, game.id, user.id),
+            None => Ok(false),
+        },
+    }
+}
+
+/// Inaccessible games are reported as missing, so their existence is not leaked
+pub fn check_access(
+    connection: &DieselConnection,
+    game: &GameEntity,
+    user: Option<&User>,
+    share_token: Option<&str>,
+) -> Result<(), &'static str> {
+    match can_access(connection, game, user, share_token) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err("Game not found"),
+        Err(e) => {
+            error!("Failed to check access to game {} {:?}", game.id, e);
+            Err("Failed to read the game")
+        }
+    }
+}
+
+pub fn has_permission(
+    connection: &DieselConnection,
+    game: &GameEntity,
+    user: &User,
+    permission: Permission,
+) -> QueryResult<bool> {
+    if is_owner(game, Some(user)) {
+        return Ok(true);
+    }
+    let role = role_of(connection, game.id, user.id)?;
+    Ok(role.map(|r| permission.granted_to(r)).unwrap_or(false))
+}
+
+/// Load the game if the user has the permission on it
+/// Like `check_access`, games without the permission are reported as missing
+pub fn load_game(
+    connection: &DieselConnection,
+    user: &User,
+    game_id: i32,
+    permission: Permission,
+) -> Result<GameEntity, &'static str> {
+    match find_game(connection, user, game_id, permission) {
+        Ok(Some(game)) => Ok(game),
+        Ok(None) => Err("Game not found"),
+        Err(e) => {
+            error!("Failed to read game {} {:?}", game_id, e);
+            Err("Failed to read the game")
+        }
+    }
+}
+
+/// Ids of the games the user collaborates on
+/// Filter with `games::id.eq_any(shared_games(user_id))`
+pub fn shared_games(
+    user_id: i32,
+) -> Filter<
+    Select<game_collaborators::table, game_collaborators::game_id>,
+    Eq<game_collaborators::user_id, i32>,
+> {
+    use crate::schema::game_collaborators::dsl;
+
+    dsl::game_collaborators
+        .select(dsl::game_id)
+        .filter(dsl::user_id.eq(user_id))
+}
+
+pub fn generate_share_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(SHARE_TOKEN_LENGTH)
+        .collect()
+}
+
+fn is_participating(
+    connection: &DieselConnection,
+    game_id: i32,
+    user_id: i32,
+) -> QueryResult<bool> {
+    use crate::schema::game_participations::dsl;
+
+    select(exists(dsl::game_participations.filter(
+        dsl::game_id.eq(game_id).and(dsl::user_id.eq(user_id)),
+    )))
+    .get_result(connection)
+}
+
+fn is_invited(connection: &DieselConnection, game_id: i32, user_id: i32) -> QueryResult<bool> {
+    use crate::schema::game_invitations::dsl;
+
+    select(exists(dsl::game_invitations.filter(
+        dsl::game_id.eq(game_id).and(dsl::user_id.eq(user_id)),
+    )))
+    .get_result(connection)
+}
+
+fn role_of(
+    connection: &DieselConnection,
+    game_id: i32,
+    user_id: i32,
+) -> QueryResult<Option<CollaboratorRole>> {
+    use crate::schema::game_collaborators::dsl;
+
+    dsl::game_collaborators
+        .find((game_id, user_id))
+        .select(dsl::role)
+        .get_result(connection)
+        .optional()
+}
+
+fn find_game(
+    connection: &DieselConnection,
+    user: &User,
+    game_id: i32,
+    permission: Permission,
+) -> QueryResult<Option<GameEntity>> {
+    use crate::schema::games::dsl::games;
+
+    let game = games
+        .find(game_id)
+        .get_result::<GameEntity>(connection)
+        .optional()?;
+    match game {
+        Some(game) if has_permission(connection, &game, user, permission)? => Ok(Some(game)),
+        _ => Ok(None),
+    }
+}
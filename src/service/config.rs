@@ -1,4 +1,5 @@
 use std::env;
+use std::time::Duration;
 
 pub struct Config {
     pub google_client_id: String,
@@ -13,6 +14,10 @@ pub struct Config {
     pub port: u16,
     pub secure: bool,
     pub auth_private_key: Option<String>,
+    /// Puzzle generation jobs give up after this much time
+    pub generation_timeout: Duration,
+    /// Number of puzzles generated at the same time
+    pub generation_workers: usize,
 }
 
 impl Config {
@@ -33,6 +38,15 @@ impl Config {
             .expect("PERPLEXIO_SECURE must be a number");
         let secure = secure != 0;
 
+        let generation_timeout = env::var("PUZZLE_GENERATION_TIMEOUT_MS")
+            .unwrap_or("10000".to_string())
+            .parse::<u64>()
+            .expect("PUZZLE_GENERATION_TIMEOUT_MS must be a number");
+        let generation_workers = env::var("PUZZLE_GENERATION_WORKERS")
+            .unwrap_or("2".to_string())
+            .parse::<usize>()
+            .expect("PUZZLE_GENERATION_WORKERS must be a number");
+
         Config {
             auth_private_key: env::var("AUTH_PRIVATE_KEY").ok(),
             google_client_id: env::var("GOOGLE_CLIENT_ID")
@@ -57,6 +71,8 @@ impl Config {
                 .expect("Port must be a 16 bit long number"),
             secure,
             heroku,
+            generation_timeout: Duration::from_millis(generation_timeout),
+            generation_workers: generation_workers.max(1),
         }
     }
 }
@@ -0,0 +1,112 @@
+//! The daily puzzle: one game a day, available for 24 hours from midnight UTC
+//! The date decides the dictionary, the words and the seed, so every instance creates the same
+//! puzzle for a given date
+use crate::entity::game_entities::{DailyPuzzleEntity, GameEntity, GameInsert, GenerationStatus};
+use crate::model::dictionary::DICTIONARIES;
+use crate::model::puzzle::Puzzle;
+use crate::service::generation::{save_puzzle, GenerationJobs};
+use crate::DieselConnection;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use diesel::dsl::insert_into;
+use diesel::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// `googleid` of the user owning the daily puzzles, created by the migrations
+pub const SYSTEM_USER: &str = "perplexio-system";
+
+/// Only these dictionaries are picked, so adding dictionaries does not change the rotation
+const DAILY_LANGUAGE: &str = "en";
+const WORD_COUNT: usize = 10;
+/// Seeds tried, one after the other, if the words do not fit with the seed of the date
+const ATTEMPTS: u64 = 5;
+
+/// Create the daily puzzles of today and tomorrow if they do not exist yet
+pub fn create_daily_puzzles(
+    connection: &DieselConnection,
+    jobs: &GenerationJobs,
+    now: DateTime<Utc>,
+) -> Result<(), String> {
+    let today = now.date().naive_utc();
+    create_daily_puzzle(connection, jobs, today)?;
+    create_daily_puzzle(connection, jobs, today.succ())
+}
+
+fn create_daily_puzzle(
+    connection: &DieselConnection,
+    jobs: &GenerationJobs,
+    date: NaiveDate,
+) -> Result<(), String> {
+    use crate::schema::daily_puzzles::dsl as d;
+    use crate::schema::games::dsl::games;
+    use crate::schema::users::dsl as u;
+
+    let exists = d::daily_puzzles
+        .find(date)
+        .get_result::<DailyPuzzleEntity>(connection)
+        .optional()
+        .map_err(|e| format!("{:?}", e))?
+        .is_some();
+    if exists {
+        return Ok(());
+    }
+
+    let seed = date.num_days_from_ce() as u64;
+    let dictionaries = DICTIONARIES
+        .iter()
+        .filter(|d| d.language == DAILY_LANGUAGE)
+        .collect::<Vec<_>>();
+    let dictionary = dictionaries[seed as usize % dictionaries.len()];
+    let words = dictionary.pick_words(WORD_COUNT, &mut StdRng::seed_from_u64(seed));
+    let puzzle = generate(jobs, words, seed)?;
+
+    let start = Utc.from_utc_date(&date).and_hms(0, 0, 0);
+    connection
+        .transaction(|| {
+            let owner_id = u::users
+                .filter(u::googleid.eq(SYSTEM_USER))
+                .select(u::id)
+                .get_result::<i32>(connection)?;
+
+            let game = insert_into(games)
+                .values(GameInsert {
+                    name: format!("Daily puzzle {}", date),
+                    owner_id: owner_id,
+                    available_from: Some(start),
+                    available_to: Some(start + Duration::days(1)),
+                    published: true,
+                    generation_status: GenerationStatus::Succeeded,
+                    description: None,
+                    tags: vec![dictionary.theme.to_string()],
+                    language: Some(dictionary.language.to_string()),
+                    difficulty: None,
+                    time_limit: None,
+                    schedule_id: None,
+                })
+                .get_result::<GameEntity>(connection)?;
+            save_puzzle(connection, game.id, owner_id, &puzzle)?;
+
+            insert_into(d::daily_puzzles)
+                .values(DailyPuzzleEntity {
+                    date: date,
+                    game_id: game.id,
+                    theme: dictionary.theme.to_string(),
+                })
+                .execute(connection)?;
+
+            info!("Created the daily puzzle of {}, game {}", date, game.id);
+            Ok(())
+        })
+        .map_err(|e: diesel::result::Error| format!("{:?}", e))
+}
+
+fn generate(jobs: &GenerationJobs, words: Vec<String>, seed: u64) -> Result<Puzzle, String> {
+    let mut error = None;
+    for attempt in 0..ATTEMPTS {
+        match jobs.generate_seeded(words.clone(), seed + attempt) {
+            Ok(puzzle) => return Ok(puzzle),
+            Err(e) => error = Some(e),
+        }
+    }
+    Err(format!("Failed to generate the daily puzzle {:?}", error))
+}
@@ -0,0 +1,252 @@
+//! Puzzle generation running in the background, outside of the request and its transaction
+use crate::entity::game_entities::{GameEntity, GameInsert, GenerationStatus};
+use crate::entity::puzzle_entities::{
+    PuzzleInsert, PuzzleUpdate, PuzzleVersionEntity, PuzzleVersionInsert,
+};
+use crate::model::puzzle::{Generator, Puzzle, PuzzleError};
+use crate::ConnectionPool;
+use crate::DieselConnection;
+use diesel::dsl::{self, insert_into, update};
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, PooledConnection};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const MAX_ITERATIONS: usize = 200;
+/// Jobs waiting for a worker, further jobs fail right away
+const QUEUE_SIZE: usize = 500;
+const QUEUE_FULL: &str = "Too many puzzles are being generated, please try again later";
+/// Wait between attempts to get a connection for saving a generated puzzle
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+struct Job {
+    game_id: i32,
+    created_by: i32,
+    words: Vec<String>,
+}
+
+/// A fixed number of workers generating the puzzles of a bounded queue
+#[derive(Clone)]
+pub struct GenerationJobs {
+    time_budget: Duration,
+    queue: SyncSender<Job>,
+    pool: ConnectionPool,
+}
+
+impl GenerationJobs {
+    pub fn new(pool: ConnectionPool, time_budget: Duration, workers: usize) -> Self {
+        let (queue, jobs) = sync_channel(QUEUE_SIZE);
+        let jobs = Arc::new(Mutex::new(jobs));
+        for _ in 0..workers {
+            let jobs = jobs.clone();
+            let pool = pool.clone();
+            thread::spawn(move || work(&jobs, &pool, time_budget));
+        }
+        Self {
+            time_budget: time_budget,
+            queue: queue,
+            pool: pool,
+        }
+    }
+
+    pub fn time_budget(&self) -> Duration {
+        self.time_budget
+    }
+
+    /// Generate a puzzle on the current thread, within the same time budget as the jobs
+    /// The same words and seed always produce the same puzzle
+    pub fn generate_seeded(&self, words: Vec<String>, seed: u64) -> Result<Puzzle, PuzzleError> {
+        Generator::new(MAX_ITERATIONS)
+            .seed(seed)
+            .deadline(Instant::now() + self.time_budget)
+            .generate(words)
+    }
+
+    /// Queue the generation of the puzzle of the game
+    /// The game's `generation_status` is expected to be `Pending` and is updated once the job
+    /// has finished, or right away if the queue is full
+    /// `created_by` is recorded as the author of the new puzzle version
+    pub fn generate_puzzle(&self, game_id: i32, created_by: i32, words: Vec<String>) {
+        let job = Job {
+            game_id: game_id,
+            created_by: created_by,
+            words: words,
+        };
+        if self.queue.try_send(job).is_err() {
+            warn!("The generation queue is full, game {} failed", game_id);
+            let result = self
+                .pool
+                .get()
+                .map_err(|e| format!("{:?}", e))
+                .and_then(|c| {
+                    fail_generation(&c, game_id, QUEUE_FULL).map_err(|e| format!("{:?}", e))
+                });
+            if let Err(e) = result {
+                error!(
+                    "Failed to mark the generation of game {} as failed: {}",
+                    game_id, e
+                );
+            }
+        }
+    }
+}
+
+fn work(jobs: &Mutex<Receiver<Job>>, pool: &ConnectionPool, time_budget: Duration) {
+    loop {
+        let job = match jobs.lock() {
+            Ok(jobs) => jobs.recv(),
+            Err(_) => return,
+        };
+        let Job {
+            game_id,
+            created_by,
+            words,
+        } = match job {
+            Ok(job) => job,
+            Err(_) => return,
+        };
+        // A panicking job fails its game instead of taking the worker down
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            Generator::new(MAX_ITERATIONS)
+                .deadline(Instant::now() + time_budget)
+                .generate(words)
+        }))
+        .unwrap_or_else(|_| {
+            error!("Generating the puzzle of game {} panicked", game_id);
+            Err(PuzzleError::InvalidArgument)
+        });
+        let connection = connection(pool, game_id);
+        finish_generation(&connection, game_id, created_by, result).unwrap_or_else(|e| {
+            error!("Failed to save the puzzle of game {}: {:?}", game_id, e);
+        });
+    }
+}
+
+/// Wait for a connection, the game would stay `Pending` if its puzzle was not saved
+fn connection(
+    pool: &ConnectionPool,
+    game_id: i32,
+) -> PooledConnection<ConnectionManager<DieselConnection>> {
+    loop {
+        match pool.get() {
+            Ok(connection) => return connection,
+            Err(e) => {
+                error!("Failed to get a connection for game {}: {:?}", game_id, e);
+                thread::sleep(RETRY_DELAY);
+            }
+        }
+    }
+}
+
+/// Insert a pending game together with its words
+/// Start generating its puzzle with `GenerationJobs::generate_puzzle` once the transaction is committed
+pub fn insert_pending_game(
+    connection: &DieselConnection,
+    game: GameInsert,
+    words: &Vec<String>,
+) -> QueryResult<GameEntity> {
+    use crate::schema::games::dsl::games;
+    use crate::schema::puzzles::dsl::puzzles;
+
+    connection.transaction(|| {
+        let game = insert_into(games)
+            .values(game)
+            .get_result::<GameEntity>(connection)?;
+
+        insert_into(puzzles)
+            .values(PuzzleInsert::pending(game.id, words))
+            .execute(connection)?;
+
+        Ok(game)
+    })
+}
+
+/// Save the result of a generation job
+pub fn finish_generation(
+    connection: &DieselConnection,
+    game_id: i32,
+    created_by: i32,
+    result: Result<Puzzle, PuzzleError>,
+) -> QueryResult<()> {
+    use crate::schema::games::dsl as g;
+
+    connection.transaction(|| {
+        let game = g::games.filter(g::id.eq(game_id));
+        match result {
+            Ok(puzzle) => {
+                save_puzzle(connection, game_id, created_by, &puzzle)?;
+                update(game)
+                    .set((
+                        g::generation_status.eq(GenerationStatus::Succeeded),
+                        g::generation_error.eq(None::<String>),
+                    ))
+                    .execute(connection)?;
+            }
+            Err(e) => {
+                warn!("Failed to generate the puzzle of game {}: {:?}", game_id, e);
+                fail_generation(connection, game_id, &e.to_string())?;
+            }
+        }
+        Ok(())
+    })
+}
+
+fn fail_generation(connection: &DieselConnection, game_id: i32, error: &str) -> QueryResult<usize> {
+    use crate::schema::games::dsl as g;
+
+    update(g::games.filter(g::id.eq(game_id)))
+        .set((
+            g::generation_status.eq(GenerationStatus::Failed),
+            g::generation_error.eq(Some(error)),
+        ))
+        .execute(connection)
+}
+
+/// Store the puzzle as the newest version of the game and make it the current one
+pub fn save_puzzle(
+    connection: &DieselConnection,
+    game_id: i32,
+    created_by: i32,
+    puzzle: &Puzzle,
+) -> QueryResult<PuzzleVersionEntity> {
+    use crate::schema::puzzle_versions::dsl as pv;
+    use crate::schema::puzzles::dsl as p;
+
+    let latest = pv::puzzle_versions
+        .filter(pv::game_id.eq(game_id))
+        .select(dsl::max(pv::version))
+        .get_result::<Option<i32>>(connection)?;
+
+    let version = insert_into(pv::puzzle_versions)
+        .values(PuzzleVersionInsert::new(
+            game_id,
+            latest.unwrap_or(0) + 1,
+            created_by,
+            puzzle,
+        ))
+        .get_result::<PuzzleVersionEntity>(connection)?;
+
+    insert_into(p::puzzles)
+        .values(PuzzleInsert::new(game_id, version.id, puzzle))
+        .on_conflict(p::game_id)
+        .do_update()
+        .set(PuzzleUpdate::new(version.id, puzzle))
+        .execute(connection)?;
+
+    Ok(version)
+}
+
+/// Jobs do not survive a restart, mark the ones left pending as failed
+pub fn fail_interrupted(connection: &DieselConnection) -> QueryResult<usize> {
+    use crate::schema::games::dsl as g;
+
+    update(g::games.filter(g::generation_status.eq(GenerationStatus::Pending)))
+        .set((
+            g::generation_status.eq(GenerationStatus::Failed),
+            g::generation_error.eq(Some("Generation was interrupted, please try again")),
+        ))
+        .execute(connection)
+}
@@ -1,4 +1,9 @@
+pub mod access;
 pub mod auth;
 pub mod config;
+pub mod daily;
 pub mod db_client;
+pub mod generation;
 pub mod pagination;
+pub mod scheduler;
+pub mod time_limit;
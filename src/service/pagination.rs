@@ -2,7 +2,7 @@ use diesel::pg::Pg;
 use diesel::prelude::*;
 use diesel::query_builder::*;
 use diesel::query_dsl::methods::LoadQuery;
-use diesel::sql_types::BigInt;
+use diesel::sql_types::{BigInt, Integer};
 
 const DEFAULT_PER_PAGE: i64 = 25;
 
@@ -66,3 +66,40 @@ where
         Ok(())
     }
 }
+
+/// Select the row of `id` from the results of the query
+/// Window functions of the query, like the rank of a row, are computed over every result
+pub trait FindRow: Sized {
+    fn find_row(self, id: i32) -> FoundRow<Self>;
+}
+
+impl<T> FindRow for T {
+    fn find_row(self, id: i32) -> FoundRow<Self> {
+        FoundRow { query: self, id }
+    }
+}
+
+#[derive(Debug, Clone, Copy, QueryId)]
+pub struct FoundRow<T> {
+    query: T,
+    id: i32,
+}
+
+impl<T: Query> Query for FoundRow<T> {
+    type SqlType = T::SqlType;
+}
+
+impl<T> RunQueryDsl<PgConnection> for FoundRow<T> {}
+
+impl<T> QueryFragment<Pg> for FoundRow<T>
+where
+    T: QueryFragment<Pg>,
+{
+    fn walk_ast(&self, mut out: AstPass<Pg>) -> QueryResult<()> {
+        out.push_sql("SELECT * FROM (");
+        self.query.walk_ast(out.reborrow())?;
+        out.push_sql(") t WHERE t.id = ");
+        out.push_bind_param::<Integer, _>(&self.id)?;
+        Ok(())
+    }
+}
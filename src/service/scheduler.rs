@@ -0,0 +1,163 @@
+//! Creates the games of recurring schedules and the daily puzzles,
+//! and closes the participations exceeding the time limit of their game
+use crate::entity::game_entities::{GameInsert, GenerationStatus};
+use crate::entity::schedule_entities::GameScheduleEntity;
+use crate::model::metadata::{escape_like, unique_name};
+use crate::service::daily;
+use crate::service::generation::{insert_pending_game, GenerationJobs};
+use crate::service::time_limit;
+use crate::ConnectionPool;
+use crate::DieselConnection;
+use chrono::{DateTime, Duration, Utc};
+use diesel::dsl::update;
+use diesel::prelude::*;
+use rand::seq::SliceRandom;
+use std::thread;
+
+/// Seconds between two checks for due schedules
+const INTERVAL_SECS: u64 = 60;
+/// Games are created this long before their period starts, so the puzzle is ready in time
+const LEAD_TIME_MINUTES: i64 = 60;
+
+/// Check for due schedules, daily puzzles and timed out participations on a background thread,
+/// for the lifetime of the process
+pub fn start(pool: ConnectionPool, jobs: GenerationJobs) {
+    thread::spawn(move || loop {
+        match pool.get() {
+            Ok(connection) => {
+                run_due(&connection, &jobs, Utc::now()).unwrap_or_else(|e| {
+                    error!("Failed to read the due schedules {:?}", e);
+                });
+                daily::create_daily_puzzles(&connection, &jobs, Utc::now()).unwrap_or_else(|e| {
+                    error!("Failed to create the daily puzzle {}", e);
+                });
+                match time_limit::close_timed_out(&connection, Utc::now()) {
+                    Ok(0) => (),
+                    Ok(closed) => info!("Closed {} timed out participations", closed),
+                    Err(e) => error!("Failed to close the timed out participations {:?}", e),
+                }
+            }
+            Err(e) => error!("Failed to get a connection for the scheduler {:?}", e),
+        }
+        thread::sleep(std::time::Duration::from_secs(INTERVAL_SECS));
+    });
+}
+
+/// Create the next game of every active schedule whose period is about to start
+pub fn run_due(
+    connection: &DieselConnection,
+    jobs: &GenerationJobs,
+    now: DateTime<Utc>,
+) -> QueryResult<()> {
+    use crate::schema::game_schedules::dsl;
+
+    let due = dsl::game_schedules
+        .filter(
+            dsl::active
+                .eq(true)
+                .and(dsl::next_start.le(now + Duration::minutes(LEAD_TIME_MINUTES))),
+        )
+        .load::<GameScheduleEntity>(connection)?;
+
+    for schedule in due {
+        run_schedule(connection, jobs, &schedule, now).unwrap_or_else(|e| {
+            error!("Failed to run schedule {}: {}", schedule.id, e);
+        });
+    }
+    Ok(())
+}
+
+fn run_schedule(
+    connection: &DieselConnection,
+    jobs: &GenerationJobs,
+    schedule: &GameScheduleEntity,
+    now: DateTime<Utc>,
+) -> Result<(), String> {
+    use crate::schema::game_schedules::dsl;
+
+    let recurrence = schedule.recurrence()?;
+    let period = recurrence.next_period(schedule.next_start);
+    let query = dsl::game_schedules.filter(dsl::id.eq(schedule.id));
+
+    if period.end <= now {
+        // Periods missed while the server was down are skipped
+        let next = recurrence.next_period(now);
+        update(query)
+            .set(dsl::next_start.eq(next.start))
+            .execute(connection)
+            .map_err(|e| format!("{:?}", e))?;
+        return Ok(());
+    }
+
+    let words = schedule
+        .words
+        .choose_multiple(&mut rand::thread_rng(), schedule.words_per_game as usize)
+        .cloned()
+        .collect::<Vec<_>>();
+    let name = format!("{} {}", schedule.name, recurrence.local_date(period.start));
+    let next = recurrence.next_period(period.start + Duration::seconds(1));
+
+    let created = connection.transaction::<_, diesel::result::Error, _>(|| {
+        let taken = taken_names(connection, schedule.owner_id, &name)?;
+        let name = if taken.contains(&name) {
+            unique_name(&name, "scheduled", &taken)
+        } else {
+            name.clone()
+        };
+        let game = GameInsert {
+            name: name,
+            owner_id: schedule.owner_id,
+            available_from: Some(period.start),
+            available_to: Some(period.end),
+            published: true,
+            generation_status: GenerationStatus::Pending,
+            description: None,
+            tags: vec![],
+            language: None,
+            difficulty: None,
+            time_limit: None,
+            schedule_id: Some(schedule.id),
+        };
+        let game = insert_pending_game(connection, game, &words)?;
+        update(query)
+            .set(dsl::next_start.eq(next.start))
+            .execute(connection)?;
+        Ok(game)
+    });
+    let game = match created {
+        Ok(game) => game,
+        Err(e) => {
+            // The period is skipped, otherwise the failing game would be retried every check
+            update(query)
+                .set(dsl::next_start.eq(next.start))
+                .execute(connection)
+                .map_err(|e| format!("{:?}", e))?;
+            return Err(format!(
+                "Failed to create the game of {}: {:?}",
+                period.start, e
+            ));
+        }
+    };
+
+    info!("Schedule {} created game {}", schedule.id, game.id);
+    jobs.generate_puzzle(game.id, schedule.owner_id, words);
+    Ok(())
+}
+
+/// Names of the owner's games starting with `name`
+fn taken_names(
+    connection: &DieselConnection,
+    owner_id: i32,
+    name: &str,
+) -> QueryResult<Vec<String>> {
+    use crate::schema::games::dsl;
+
+    dsl::games
+        .filter(
+            dsl::owner_id
+                .eq(owner_id)
+                .and(dsl::name.like(format!("{}%", escape_like(name)))),
+        )
+        .select(dsl::name)
+        .load(connection)
+}
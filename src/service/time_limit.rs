@@ -0,0 +1,87 @@
+//! Participations exceeding the time limit of their game are closed as timed out
+//! Checked when a solution is submitted, and by the scheduler for participations left open
+use crate::entity::game_entities::{GameEntity, ParticipationOutcome};
+use crate::model::participation::{
+    close_interval, deadline, is_timed_out, GameParticipationEntity,
+};
+use crate::schema::game_participations;
+use crate::DieselConnection;
+use chrono::{DateTime, Utc};
+use diesel::dsl::update;
+use diesel::prelude::*;
+
+/// Close every participation in progress that exceeded the time limit of its game
+pub fn close_timed_out(connection: &DieselConnection, now: DateTime<Utc>) -> QueryResult<usize> {
+    use crate::schema::game_participations::dsl as gp;
+    use crate::schema::games::dsl as g;
+
+    let open = gp::game_participations
+        .inner_join(g::games)
+        .filter(gp::end_time.is_null().and(g::time_limit.is_not_null()))
+        .select((game_participations::all_columns, g::time_limit))
+        .load::<(GameParticipationEntity, Option<i32>)>(connection)?;
+
+    let mut closed = 0;
+    for (participation, time_limit) in open {
+        match time_limit {
+            Some(limit) if is_timed_out(&participation, time_limit, now) => {
+                closed += time_out(connection, &participation, limit)?;
+            }
+            _ => (),
+        }
+    }
+    Ok(closed)
+}
+
+/// Reject solutions of timed out participations, closing the participation if it is not yet
+/// `participation` is the current participation of the player, see `current_participation`
+pub fn check_time_limit(
+    connection: &DieselConnection,
+    game: &GameEntity,
+    participation: Option<&GameParticipationEntity>,
+    now: DateTime<Utc>,
+) -> Result<(), &'static str> {
+    let timed_out = match (participation, game.time_limit) {
+        (Some(p), _) if p.outcome == Some(ParticipationOutcome::TimedOut) => Ok(true),
+        (Some(p), Some(limit)) if is_timed_out(p, game.time_limit, now) => {
+            time_out(connection, p, limit).map(|_| true)
+        }
+        _ => Ok(false),
+    };
+    match timed_out {
+        Ok(false) => Ok(()),
+        Ok(true) => Err("The time limit of the game is over"),
+        Err(e) => {
+            error!(
+                "Failed to time out the participation of game {} {:?}",
+                game.id, e
+            );
+            Err("Failed to close the participation")
+        }
+    }
+}
+
+/// End the participation at its deadline, solutions found until then are kept
+fn time_out(
+    connection: &DieselConnection,
+    participation: &GameParticipationEntity,
+    time_limit: i32,
+) -> QueryResult<usize> {
+    use crate::schema::game_participations::dsl as gp;
+
+    let end_time = deadline(participation.start_time, time_limit);
+    connection.transaction(|| {
+        close_interval(connection, participation.id, end_time)?;
+        update(
+            gp::game_participations
+                .find(participation.id)
+                .filter(gp::end_time.is_null()),
+        )
+        .set((
+            gp::end_time.eq(end_time),
+            gp::duration.eq(time_limit * 1000),
+            gp::outcome.eq(ParticipationOutcome::TimedOut),
+        ))
+        .execute(connection)
+    })
+}